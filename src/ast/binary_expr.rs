@@ -1,21 +1,44 @@
-use crate::ast::expr::Expression;
 use std::fmt::{Display, Formatter};
 
 #[derive(Debug, Clone)]
 pub enum BinaryExpressionType {
     Add,
+    /// SWF4 `ActionAdd`, always numeric
+    NumericAdd,
     Subtract,
     Divide,
     Multiply,
     Modulo,
+    /// SWF4 `ActionStringAdd`, `add`
+    StringAdd,
     Equals,
     NotEquals,
+    /// SWF4 `ActionEquals`, always numeric
+    NumericEquals,
+    NumericNotEquals,
+    /// SWF4 `ActionStringEquals`, `eq`
+    StringEquals,
+    /// `ne`
+    StringNotEquals,
     StrictEquals,
     NotStrictEquals,
     LogicalAnd,
     LogicalOr,
     Less,
     Greater,
+    LessEquals,
+    GreaterEquals,
+    InstanceOf,
+    /// SWF4 `ActionLess`, always numeric
+    NumericLess,
+    /// SWF4 `ActionStringLess`, `lt`
+    StringLess,
+    /// `ActionStringGreater`, `gt`
+    StringGreater,
+    /// `le`
+    StringLessEquals,
+    /// `ge`
+    StringGreaterEquals,
     BitAnd,
     BitLShift,
     BitOr,
@@ -24,6 +47,69 @@ pub enum BinaryExpressionType {
     BitXor,
 }
 
+impl BinaryExpressionType {
+    /// SWF4 operators that convert both operands to numbers
+    pub fn is_numeric(&self) -> bool {
+        matches!(
+            self,
+            BinaryExpressionType::NumericAdd
+                | BinaryExpressionType::NumericEquals
+                | BinaryExpressionType::NumericNotEquals
+                | BinaryExpressionType::NumericLess
+        )
+    }
+
+    /// The comparison `!(a op b)` folds into, which is only
+    /// equivalent if neither operand is `NaN`
+    pub fn negated_comparison(&self) -> Option<BinaryExpressionType> {
+        match self {
+            BinaryExpressionType::Less => Some(BinaryExpressionType::GreaterEquals),
+            BinaryExpressionType::Greater => Some(BinaryExpressionType::LessEquals),
+            _ => None,
+        }
+    }
+
+    /// The operator that produces the opposite boolean result, if any
+    pub fn negated(&self) -> Option<BinaryExpressionType> {
+        match self {
+            BinaryExpressionType::Equals => Some(BinaryExpressionType::NotEquals),
+            BinaryExpressionType::NotEquals => Some(BinaryExpressionType::Equals),
+            BinaryExpressionType::StrictEquals => Some(BinaryExpressionType::NotStrictEquals),
+            BinaryExpressionType::NotStrictEquals => Some(BinaryExpressionType::StrictEquals),
+            BinaryExpressionType::NumericEquals => Some(BinaryExpressionType::NumericNotEquals),
+            BinaryExpressionType::NumericNotEquals => Some(BinaryExpressionType::NumericEquals),
+            BinaryExpressionType::StringEquals => Some(BinaryExpressionType::StringNotEquals),
+            BinaryExpressionType::StringNotEquals => Some(BinaryExpressionType::StringEquals),
+            BinaryExpressionType::StringLess => Some(BinaryExpressionType::StringGreaterEquals),
+            BinaryExpressionType::StringGreaterEquals => Some(BinaryExpressionType::StringLess),
+            BinaryExpressionType::StringGreater => Some(BinaryExpressionType::StringLessEquals),
+            BinaryExpressionType::StringLessEquals => Some(BinaryExpressionType::StringGreater),
+            // these only ever come from negating `>` and `<`
+            BinaryExpressionType::LessEquals => Some(BinaryExpressionType::Greater),
+            BinaryExpressionType::GreaterEquals => Some(BinaryExpressionType::Less),
+            _ => None,
+        }
+    }
+
+    /// Operators that always produce a number
+    pub fn is_arithmetic(&self) -> bool {
+        matches!(
+            self,
+            BinaryExpressionType::NumericAdd
+                | BinaryExpressionType::Subtract
+                | BinaryExpressionType::Multiply
+                | BinaryExpressionType::Divide
+                | BinaryExpressionType::Modulo
+                | BinaryExpressionType::BitAnd
+                | BinaryExpressionType::BitOr
+                | BinaryExpressionType::BitXor
+                | BinaryExpressionType::BitLShift
+                | BinaryExpressionType::BitRShift
+                | BinaryExpressionType::BitURShift
+        )
+    }
+}
+
 impl Display for BinaryExpressionType {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         write!(
@@ -32,7 +118,20 @@ impl Display for BinaryExpressionType {
             match self {
                 BinaryExpressionType::Less => "<",
                 BinaryExpressionType::Greater => ">",
-                BinaryExpressionType::Add => "+",
+                BinaryExpressionType::LessEquals => "<=",
+                BinaryExpressionType::InstanceOf => "instanceof",
+                BinaryExpressionType::GreaterEquals => ">=",
+                BinaryExpressionType::StringLessEquals => "le",
+                BinaryExpressionType::StringGreaterEquals => "ge",
+                BinaryExpressionType::Add | BinaryExpressionType::NumericAdd => "+",
+                BinaryExpressionType::StringAdd => "add",
+                BinaryExpressionType::NumericEquals => "==",
+                BinaryExpressionType::NumericNotEquals => "!=",
+                BinaryExpressionType::NumericLess => "<",
+                BinaryExpressionType::StringEquals => "eq",
+                BinaryExpressionType::StringNotEquals => "ne",
+                BinaryExpressionType::StringLess => "lt",
+                BinaryExpressionType::StringGreater => "gt",
                 BinaryExpressionType::BitOr => "|",
                 BinaryExpressionType::BitAnd => "&",
                 BinaryExpressionType::BitLShift => "<<",
@@ -18,8 +18,5 @@ impl Display for Block {
 }
 
 fn indent(str: String) -> String {
-    str.split('\n')
-        .into_iter()
-        .map(|x| format!("  {}", x))
-        .join("\n")
+    str.split('\n').map(|x| format!("  {}", x)).join("\n")
 }
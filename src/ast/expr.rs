@@ -1,5 +1,11 @@
 use crate::ast::binary_expr::BinaryExpressionType;
 use crate::ast::block::Block;
+use crate::ast::identifier::{
+    is_array_index, is_valid_identifier, is_valid_variable_name, Name, SetName,
+};
+use crate::ast::literal::quote_string;
+use crate::ast::precedence;
+use crate::ast::property::Property;
 use crate::ast::variant::Variant;
 use itertools::Itertools;
 use std::fmt::{Display, Formatter};
@@ -37,7 +43,7 @@ pub enum Expression {
     },
     GetProperty {
         path: Box<Expression>,
-        index: Box<Expression>,
+        property: Property,
     },
     Ternary {
         condition: Box<Expression>,
@@ -63,10 +69,96 @@ pub enum Expression {
         name: ReferenceExpression,
         args: Vec<Expression>,
     },
+    New {
+        constructor: Box<Expression>,
+        args: Vec<Expression>,
+    },
     StoreRegister {
         id: u8,
         value: Box<Expression>,
     },
+    Assign {
+        target: Box<Expression>,
+        operator: Option<BinaryExpressionType>,
+        value: Box<Expression>,
+    },
+}
+
+impl Expression {
+    /// Whether evaluating this expression twice is indistinguishable
+    /// from evaluating it once, i.e. literals and plain identifiers.
+    pub fn is_pure(&self) -> bool {
+        match self {
+            Expression::Literal(Variant::Array(_) | Variant::Object(_)) => false,
+            Expression::Literal(_) => true,
+            Expression::Reference(ReferenceExpression::Expression(_)) => false,
+            Expression::Reference(_) => true,
+            _ => false,
+        }
+    }
+
+    /// Whether the expression always evaluates to a number
+    pub fn is_number(&self) -> bool {
+        match self {
+            Expression::Literal(Variant::Int(_) | Variant::Float(_) | Variant::Double(_)) => true,
+            Expression::Binary {
+                expression_type, ..
+            } => expression_type.is_arithmetic(),
+            Expression::Unary {
+                expression_type, ..
+            } => !matches!(
+                expression_type,
+                UnaryExpressionType::Not
+                    | UnaryExpressionType::ToString
+                    | UnaryExpressionType::TypeOf
+                    | UnaryExpressionType::Delete
+            ),
+            _ => false,
+        }
+    }
+
+    /// Prints the expression with an explicit `Number()` coercion where needed
+    fn to_number_string(&self, precedence: u8) -> String {
+        if self.is_number() {
+            self.at(precedence).to_string()
+        } else {
+            format!("Number({})", self)
+        }
+    }
+
+    /// Whether the expression or an object in its member chain is printed as a call
+    fn has_call_in_chain(&self) -> bool {
+        match self {
+            Expression::CallFunction { .. } | Expression::CallMethod { .. } => true,
+            Expression::GetProperty { path, property } => property.as_member_of(path).is_none(),
+            Expression::GetMember {
+                object: ReferenceExpression::Expression(object),
+                ..
+            } => object.has_call_in_chain(),
+            Expression::Reference(ReferenceExpression::Expression(expression)) => {
+                expression.has_call_in_chain()
+            }
+            _ => false,
+        }
+    }
+
+    /// Whether both expressions refer to the same variable, register or member
+    pub fn is_same_target(&self, other: &Expression) -> bool {
+        match (self, other) {
+            (Expression::Reference(a), Expression::Reference(b)) => a.is_same_target(b),
+            (
+                Expression::GetMember {
+                    object: object_a,
+                    name: name_a,
+                },
+                Expression::GetMember {
+                    object: object_b,
+                    name: name_b,
+                },
+            ) => object_a.is_same_target(object_b) && name_a.is_same_target(name_b),
+            _ => false,
+        }
+    }
 }
 
 impl Display for Expression {
@@ -76,8 +168,49 @@ impl Display for Expression {
                 condition,
                 if_false,
                 if_true,
-            } => write!(f, "{} ? {} : {}", condition, if_true, if_false),
-            Expression::StoreRegister { id, value } => write!(f, "${} = {}", id, value),
+            } => write!(
+                f,
+                "{} ? {} : {}",
+                condition.at(precedence::TERNARY + 1),
+                if_true.at(precedence::ASSIGNMENT),
+                if_false.at(precedence::ASSIGNMENT)
+            ),
+            Expression::StoreRegister { id, value } => {
+                write!(f, "${} = {}", id, value.at(precedence::ASSIGNMENT))
+            }
+            Expression::Assign {
+                target,
+                operator,
+                value,
+            } => match (&**target, operator) {
+                (Expression::Reference(ReferenceExpression::Identifier(name)), None) => {
+                    write!(f, "{}", SetName(name, value.at(precedence::ASSIGNMENT)))
+                }
+                (Expression::Reference(ReferenceExpression::Identifier(name)), Some(operator))
+                    if !is_valid_variable_name(name) =>
+                {
+                    let value = format!(
+                        "{} {} {}",
+                        Name(name),
+                        operator,
+                        value.at(operator.precedence() + 1)
+                    );
+                    write!(f, "{}", SetName(name, value))
+                }
+                (_, Some(operator)) => write!(
+                    f,
+                    "{} {}= {}",
+                    target.at(precedence::POSTFIX),
+                    operator,
+                    value.at(precedence::ASSIGNMENT)
+                ),
+                (_, None) => write!(
+                    f,
+                    "{} = {}",
+                    target.at(precedence::POSTFIX),
+                    value.at(precedence::ASSIGNMENT)
+                ),
+            },
             Expression::Superposition(superpositions) => {
                 write!(
                     f,
@@ -86,42 +219,68 @@ impl Display for Expression {
                 )
             }
             Expression::Reference(reference) => write!(f, "{}", reference),
-            Expression::GetMember { object, name } => match name.clone() {
-                ReferenceExpression::Identifier(identifier) => {
-                    write!(f, "{}.{}", object, identifier)
-                }
-                ReferenceExpression::Variable(var) => {
-                    write!(f, "{}[{}]", name, var)
-                }
-                ReferenceExpression::Register(reg) => write!(f, "{}[${}]", object, reg),
-                ReferenceExpression::Expression(expr) => write!(f, "{}[{}]", object, expr),
+            Expression::GetMember { object, name } => write!(f, "{}", Member(object, name)),
+            Expression::GetProperty { path, property } => match property.as_member_of(path) {
+                Some(member) => write!(f, "{}", member),
+                None => write!(f, "getProperty({}, {})", path, property),
             },
-            Expression::GetProperty { path, index } => {
-                write!(f, "getProperty({}, {})", path, index)
-            }
             Expression::CallMethod { object, name, args } => {
-                let args: Vec<String> = args.iter().map(|it| it.to_string()).collect();
-                match name.clone() {
-                    ReferenceExpression::Identifier(identifier) => {
-                        write!(f, "{}.{}({})", object, identifier, args.join(", "))
-                    }
-                    ReferenceExpression::Variable(var) => {
-                        write!(f, "{}[{}]({})", name, var, args.join(", "))
-                    }
-                    ReferenceExpression::Register(reg) => {
-                        write!(f, "{}[${}]({})", object, reg, args.join(", "))
-                    }
-                    ReferenceExpression::Expression(expr) => {
-                        write!(f, "{}[{}]({})", object, expr, args.join(", "))
-                    }
-                }
+                write!(f, "{}({})", Member(object, name), join_args(args))
+            }
+            // `new f()()` would call `new f()` instead of constructing `f()`
+            Expression::New { constructor, args } if constructor.has_call_in_chain() => {
+                write!(f, "new ({})({})", constructor, join_args(args))
+            }
+            Expression::New { constructor, args } => {
+                write!(
+                    f,
+                    "new {}({})",
+                    constructor.at(precedence::MEMBER),
+                    join_args(args)
+                )
             }
             Expression::Literal(literal) => write!(f, "{}", literal),
             Expression::Binary {
                 left,
                 right,
                 expression_type,
-            } => write!(f, "({} {} {})", left, expression_type, right),
+            } if expression_type.is_numeric() => {
+                let precedence = expression_type.precedence();
+                write!(
+                    f,
+                    "{} {} {}",
+                    left.to_number_string(precedence),
+                    expression_type,
+                    right.to_number_string(precedence + 1)
+                )
+            }
+            Expression::Binary {
+                left,
+                right,
+                expression_type,
+            } => {
+                let precedence = expression_type.precedence();
+                write!(
+                    f,
+                    "{} {} {}",
+                    left.at(precedence),
+                    expression_type,
+                    right.at(precedence + 1)
+                )
+            }
+            Expression::Function {
+                identifier: Some(name),
+                parameters,
+                body,
+                ..
+            } if !is_valid_variable_name(name) => {
+                let function = format!(
+                    "function ({}) {}",
+                    parameters.iter().map(Parameter).join(", "),
+                    body
+                );
+                write!(f, "{}", SetName(name, function.trim_end()))
+            }
             Expression::Function {
                 identifier,
                 parameters,
@@ -130,38 +289,116 @@ impl Display for Expression {
             } => write!(
                 f,
                 "function {}({}) {}",
-                if let Some(name) = identifier {
-                    name
-                } else {
-                    ""
-                },
-                parameters.iter().map(|p| p.to_string()).join(", "),
+                identifier.as_deref().unwrap_or_default(),
+                parameters.iter().map(Parameter).join(", "),
                 body
             ),
             Expression::Unary {
                 target,
                 expression_type,
             } => match expression_type {
-                UnaryExpressionType::Increment => write!(f, "{}++", target),
-                UnaryExpressionType::Decrement => write!(f, "{}--", target),
-                UnaryExpressionType::Not => write!(f, "!{}", target),
+                // unlike `+`, `ActionIncrement` converts strings to numbers
+                UnaryExpressionType::Increment => {
+                    write!(f, "{} + 1", target.to_number_string(precedence::ADDITIVE))
+                }
+                UnaryExpressionType::Decrement => {
+                    write!(f, "{} - 1", target.to_number_string(precedence::ADDITIVE))
+                }
+                UnaryExpressionType::PreIncrement => {
+                    write!(f, "++{}", target.at(precedence::POSTFIX))
+                }
+                UnaryExpressionType::PreDecrement => {
+                    write!(f, "--{}", target.at(precedence::POSTFIX))
+                }
+                UnaryExpressionType::PostIncrement => {
+                    write!(f, "{}++", target.at(precedence::POSTFIX))
+                }
+                UnaryExpressionType::PostDecrement => {
+                    write!(f, "{}--", target.at(precedence::POSTFIX))
+                }
+                UnaryExpressionType::Not => write!(f, "!{}", target.at(precedence::PREFIX)),
+                UnaryExpressionType::Negate => {
+                    let target = target.at(precedence::PREFIX).to_string();
+                    if target.starts_with('-') {
+                        write!(f, "-({})", target)
+                    } else {
+                        write!(f, "-{}", target)
+                    }
+                }
+                UnaryExpressionType::TypeOf => {
+                    write!(f, "typeof {}", target.at(precedence::PREFIX))
+                }
+                UnaryExpressionType::Delete => {
+                    write!(f, "delete {}", target.at(precedence::PREFIX))
+                }
                 UnaryExpressionType::ToInteger => write!(f, "int({})", target),
                 UnaryExpressionType::ToString => write!(f, "String({})", target),
                 UnaryExpressionType::ToNumber => write!(f, "Number({})", target),
             },
             Expression::CallFunction { name, args } => {
-                let args: Vec<String> = args.iter().map(|it| it.to_string()).collect();
-                write!(f, "{}({})", name, args.join(", "))
+                write!(f, "{}({})", name.at(precedence::MEMBER), join_args(args))
+            }
+        }
+    }
+}
+
+fn join_args(args: &[Expression]) -> String {
+    args.iter()
+        .map(|it| it.at(precedence::ASSIGNMENT).to_string())
+        .join(", ")
+}
+
+/// Prints a function parameter. Names that can't be identifiers have
+/// no source form, so they are left in a comment after a placeholder.
+struct Parameter<'a>(&'a ReferenceExpression);
+
+impl Display for Parameter<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self.0 {
+            ReferenceExpression::Identifier(name) if !is_valid_variable_name(name) => {
+                write!(f, "_ /* {} */", quote_string(name))
+            }
+            parameter => write!(f, "{}", parameter),
+        }
+    }
+}
+
+/// Prints `object.name`, or `object[name]` for computed names
+pub struct Member<'a>(pub &'a ReferenceExpression, pub &'a ReferenceExpression);
+
+impl Display for Member<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let Member(object, name) = self;
+        let object = object.at(precedence::MEMBER);
+        match name {
+            ReferenceExpression::Identifier(identifier) if is_valid_identifier(identifier) => {
+                write!(f, "{}.{}", object, identifier)
+            }
+            ReferenceExpression::Identifier(index) if is_array_index(index) => {
+                write!(f, "{}[{}]", object, index)
             }
+            ReferenceExpression::Identifier(key) => write!(f, "{}[{}]", object, quote_string(key)),
+            ReferenceExpression::Variable(var) => write!(f, "{}[{}]", object, var),
+            ReferenceExpression::Register(reg) => write!(f, "{}[${}]", object, reg),
+            ReferenceExpression::Expression(expr) => write!(f, "{}[{}]", object, expr),
         }
     }
 }
 
 #[derive(Debug, Clone)]
 pub enum UnaryExpressionType {
+    /// `ActionIncrement`, only computes the value without storing it
     Increment,
+    /// `ActionDecrement`, only computes the value without storing it
     Decrement,
+    PreIncrement,
+    PreDecrement,
+    PostIncrement,
+    PostDecrement,
     Not,
+    Negate,
+    TypeOf,
+    Delete,
     ToInteger,
     ToString,
     ToNumber,
@@ -185,10 +422,27 @@ impl ReferenceExpression {
     }
 }
 
+impl ReferenceExpression {
+    pub fn is_same_target(&self, other: &ReferenceExpression) -> bool {
+        match (self, other) {
+            (
+                ReferenceExpression::Identifier(a) | ReferenceExpression::Variable(a),
+                ReferenceExpression::Identifier(b) | ReferenceExpression::Variable(b),
+            ) => a == b,
+            (ReferenceExpression::Register(a), ReferenceExpression::Register(b)) => a == b,
+            (ReferenceExpression::Expression(a), ReferenceExpression::Expression(b)) => {
+                a.is_same_target(b)
+            }
+            _ => false,
+        }
+    }
+}
+
 impl Display for ReferenceExpression {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         match self {
-            ReferenceExpression::Identifier(it) => write!(f, "{}", it),
+            ReferenceExpression::Identifier(name) => write!(f, "{}", Name(name)),
+            // slash syntax paths from `--keep-slash-syntax` are printed as they are
             ReferenceExpression::Variable(var) => write!(f, "{}", var),
             ReferenceExpression::Expression(expr) => write!(f, "{}", expr),
             ReferenceExpression::Register(reg) => write!(f, "${}", reg),
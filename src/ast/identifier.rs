@@ -0,0 +1,111 @@
+use crate::ast::literal::quote_string;
+use std::fmt::{Display, Formatter};
+
+/// Words that can't be used as identifiers or after a dot,
+/// including the SWF4 string operators like `eq` and `add`
+const RESERVED_WORDS: [&str; 49] = [
+    "add",
+    "and",
+    "break",
+    "case",
+    "catch",
+    "class",
+    "continue",
+    "default",
+    "delete",
+    "do",
+    "dynamic",
+    "else",
+    "eq",
+    "extends",
+    "false",
+    "finally",
+    "for",
+    "function",
+    "ge",
+    "gt",
+    "if",
+    "implements",
+    "import",
+    "in",
+    "instanceof",
+    "interface",
+    "intrinsic",
+    "le",
+    "lt",
+    "ne",
+    "new",
+    "not",
+    "null",
+    "or",
+    "private",
+    "public",
+    "return",
+    "static",
+    "super",
+    "switch",
+    "this",
+    "throw",
+    "true",
+    "try",
+    "typeof",
+    "var",
+    "void",
+    "while",
+    "with",
+];
+
+pub fn is_valid_identifier(name: &str) -> bool {
+    is_identifier_name(name) && !RESERVED_WORDS.contains(&name)
+}
+
+/// Whether `name` is made of identifier characters, reserved words included
+pub fn is_identifier_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|first| first.is_alphabetic() || first == '_' || first == '$')
+        && chars.all(|it| it.is_alphanumeric() || it == '_' || it == '$')
+}
+
+/// Like [is_valid_identifier], but `this` and `super` are fine on their own
+pub fn is_valid_variable_name(name: &str) -> bool {
+    matches!(name, "this" | "super") || is_valid_identifier(name)
+}
+
+/// Whether `name` is how ActionScript prints an integer index, like `0` or `12`,
+/// so that `obj["12"]` can be written as `obj[12]`
+pub fn is_array_index(name: &str) -> bool {
+    name.parse::<u32>()
+        .is_ok_and(|index| index.to_string() == name)
+}
+
+/// Prints a variable or function name, or `eval("name")` for names
+/// that can't be written as an identifier, like `a-b` or `if`
+pub struct Name<'a>(pub &'a str);
+
+impl Display for Name<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let Name(name) = self;
+        if is_valid_variable_name(name) {
+            write!(f, "{}", name)
+        } else {
+            write!(f, "eval({})", quote_string(name))
+        }
+    }
+}
+
+/// Prints `name = value`, or `set("name", value)` if `name`
+/// can't be written as an identifier
+pub struct SetName<'a, T>(pub &'a str, pub T);
+
+impl<T: Display> Display for SetName<'_, T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let SetName(name, value) = self;
+        if is_valid_variable_name(name) {
+            write!(f, "{} = {}", name, value)
+        } else {
+            write!(f, "set({}, {})", quote_string(name), value)
+        }
+    }
+}
@@ -0,0 +1,151 @@
+use std::fmt::Write;
+
+/// Prints a string literal with ActionScript escapes
+pub fn quote_string(value: &str) -> String {
+    quote(value.chars().map(Ok))
+}
+
+/// Like [quote_string], for strings that contain lone surrogates
+pub fn quote_utf16(units: &[u16]) -> String {
+    quote(
+        char::decode_utf16(units.iter().copied())
+            .map(|it| it.map_err(|it| it.unpaired_surrogate())),
+    )
+}
+
+fn quote(chars: impl Iterator<Item = Result<char, u16>>) -> String {
+    let mut quoted = String::from('"');
+    for char in chars {
+        match char {
+            Ok('"') => quoted.push_str("\\\""),
+            Ok('\\') => quoted.push_str("\\\\"),
+            Ok('\n') => quoted.push_str("\\n"),
+            Ok('\r') => quoted.push_str("\\r"),
+            Ok('\t') => quoted.push_str("\\t"),
+            Ok('\u{8}') => quoted.push_str("\\b"),
+            Ok('\u{c}') => quoted.push_str("\\f"),
+            Ok(char) if char.is_control() || matches!(char, '\u{2028}' | '\u{2029}') => {
+                let _ = write!(quoted, "\\u{:04X}", char as u32);
+            }
+            Ok(char) => quoted.push(char),
+            Err(surrogate) => {
+                let _ = write!(quoted, "\\u{:04X}", surrogate);
+            }
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+pub fn format_f64(value: f64) -> String {
+    format_number(
+        value.is_nan(),
+        value.is_infinite(),
+        value.is_sign_negative(),
+        || format!("{:e}", value.abs()),
+    )
+}
+
+/// Uses the shortest digits that round-trip through `f32`,
+/// so `0.1f32` prints as `0.1` rather than `0.10000000149011612`
+pub fn format_f32(value: f32) -> String {
+    format_number(
+        value.is_nan(),
+        value.is_infinite(),
+        value.is_sign_negative(),
+        || format!("{:e}", value.abs()),
+    )
+}
+
+fn format_number<F>(nan: bool, infinite: bool, negative: bool, exponential: F) -> String
+where
+    F: FnOnce() -> String,
+{
+    let sign = if negative { "-" } else { "" };
+    if nan {
+        "NaN".to_string()
+    } else if infinite {
+        format!("{}Infinity", sign)
+    } else {
+        format!("{}{}", sign, js_notation(&exponential()))
+    }
+}
+
+/// Lays out the digits of Rust's shortest `{:e}` output the way
+/// JavaScript's `Number.prototype.toString` does
+fn js_notation(exponential: &str) -> String {
+    let (mantissa, exponent) = exponential
+        .split_once('e')
+        .expect("LowerExp always has an exponent");
+    let digits: String = mantissa.chars().filter(|it| *it != '.').collect();
+    let exponent: i32 = exponent.parse().expect("LowerExp exponent is an integer");
+    if digits == "0" {
+        return "0".to_string();
+    }
+
+    // position of the decimal point relative to the digits
+    let point = exponent + 1;
+    let length = digits.len() as i32;
+    if length <= point && point <= 21 {
+        format!("{}{}", digits, "0".repeat((point - length) as usize))
+    } else if 0 < point && point <= 21 {
+        let (integer, fraction) = digits.split_at(point as usize);
+        format!("{}.{}", integer, fraction)
+    } else if -6 < point && point <= 0 {
+        format!("0.{}{}", "0".repeat(-point as usize), digits)
+    } else {
+        let (first, rest) = digits.split_at(1);
+        let sign = if exponent < 0 { "-" } else { "+" };
+        if rest.is_empty() {
+            format!("{}e{}{}", first, sign, exponent.abs())
+        } else {
+            format!("{}.{}e{}{}", first, rest, sign, exponent.abs())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn switches_to_exponents_like_javascript() {
+        assert_eq!(format_f64(1e20), "100000000000000000000");
+        assert_eq!(format_f64(1e21), "1e+21");
+        assert_eq!(format_f64(1.5e21), "1.5e+21");
+        assert_eq!(format_f64(0.000001), "0.000001");
+        assert_eq!(format_f64(1e-7), "1e-7");
+        assert_eq!(format_f64(1.25e-7), "1.25e-7");
+        assert_eq!(format_f64(123.456), "123.456");
+        assert_eq!(format_f32(0.1), "0.1");
+    }
+
+    #[test]
+    fn formats_special_numbers() {
+        assert_eq!(format_f64(0.0), "0");
+        assert_eq!(format_f64(-0.0), "-0");
+        assert_eq!(format_f64(f64::NAN), "NaN");
+        assert_eq!(format_f64(f64::INFINITY), "Infinity");
+        assert_eq!(format_f64(f64::NEG_INFINITY), "-Infinity");
+        assert_eq!(format_f32(f32::NEG_INFINITY), "-Infinity");
+    }
+
+    #[test]
+    fn escapes_control_characters() {
+        assert_eq!(quote_string("a\"b\\c"), r#""a\"b\\c""#);
+        assert_eq!(quote_string("\n\r\t\u{8}\u{c}"), r#""\n\r\t\b\f""#);
+        assert_eq!(quote_string("\u{1}\u{7f}"), r#""\u0001\u007F""#);
+        assert_eq!(quote_string("\u{2028}\u{2029}"), r#""\u2028\u2029""#);
+        assert_eq!(quote_string("é😀"), "\"é😀\"");
+    }
+
+    #[test]
+    fn escapes_lone_surrogates() {
+        assert_eq!(quote_utf16(&[0xD800, 0x61, 0xDFFF]), r#""\uD800a\uDFFF""#);
+        assert_eq!(
+            quote_utf16(&[0xDBFF, 0xDBFF, 0xDC00]),
+            "\"\\uDBFF\u{10FC00}\""
+        );
+        assert_eq!(quote_string("\u{F0041}"), "\"\u{F0041}\"");
+    }
+}
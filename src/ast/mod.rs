@@ -2,5 +2,10 @@ pub mod action;
 pub mod binary_expr;
 pub mod block;
 pub mod expr;
+pub mod identifier;
+pub mod literal;
+pub mod precedence;
+pub mod property;
 pub mod statement;
 pub mod variant;
+pub mod visit;
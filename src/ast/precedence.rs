@@ -0,0 +1,153 @@
+use crate::ast::binary_expr::BinaryExpressionType;
+use crate::ast::expr::{Expression, ReferenceExpression, UnaryExpressionType};
+use crate::ast::variant::Variant;
+use std::fmt::{Display, Formatter};
+
+/// ActionScript operator precedence, higher binds tighter
+pub const LOWEST: u8 = 0;
+pub const ASSIGNMENT: u8 = 3;
+pub const TERNARY: u8 = 4;
+pub const LOGICAL_OR: u8 = 5;
+pub const LOGICAL_AND: u8 = 6;
+pub const BIT_OR: u8 = 7;
+pub const BIT_XOR: u8 = 8;
+pub const BIT_AND: u8 = 9;
+pub const EQUALITY: u8 = 10;
+pub const RELATIONAL: u8 = 11;
+pub const SHIFT: u8 = 12;
+pub const ADDITIVE: u8 = 13;
+pub const MULTIPLICATIVE: u8 = 14;
+pub const PREFIX: u8 = 16;
+pub const POSTFIX: u8 = 17;
+pub const MEMBER: u8 = 19;
+pub const PRIMARY: u8 = 20;
+
+impl BinaryExpressionType {
+    pub fn precedence(&self) -> u8 {
+        match self {
+            BinaryExpressionType::LogicalOr => LOGICAL_OR,
+            BinaryExpressionType::LogicalAnd => LOGICAL_AND,
+            BinaryExpressionType::BitOr => BIT_OR,
+            BinaryExpressionType::BitXor => BIT_XOR,
+            BinaryExpressionType::BitAnd => BIT_AND,
+            BinaryExpressionType::Equals
+            | BinaryExpressionType::NotEquals
+            | BinaryExpressionType::StrictEquals
+            | BinaryExpressionType::NotStrictEquals
+            | BinaryExpressionType::NumericEquals
+            | BinaryExpressionType::NumericNotEquals
+            | BinaryExpressionType::StringEquals
+            | BinaryExpressionType::StringNotEquals => EQUALITY,
+            BinaryExpressionType::Less
+            | BinaryExpressionType::Greater
+            | BinaryExpressionType::InstanceOf
+            | BinaryExpressionType::LessEquals
+            | BinaryExpressionType::GreaterEquals
+            | BinaryExpressionType::NumericLess
+            | BinaryExpressionType::StringLess
+            | BinaryExpressionType::StringGreater
+            | BinaryExpressionType::StringLessEquals
+            | BinaryExpressionType::StringGreaterEquals => RELATIONAL,
+            BinaryExpressionType::BitLShift
+            | BinaryExpressionType::BitRShift
+            | BinaryExpressionType::BitURShift => SHIFT,
+            BinaryExpressionType::Add
+            | BinaryExpressionType::NumericAdd
+            | BinaryExpressionType::StringAdd
+            | BinaryExpressionType::Subtract => ADDITIVE,
+            BinaryExpressionType::Multiply
+            | BinaryExpressionType::Divide
+            | BinaryExpressionType::Modulo => MULTIPLICATIVE,
+        }
+    }
+}
+
+impl Expression {
+    /// How tightly the printed form of this expression binds
+    pub fn precedence(&self) -> u8 {
+        match self {
+            Expression::Superposition(_) => LOWEST,
+            Expression::Function { .. } => ASSIGNMENT,
+            Expression::StoreRegister { .. } | Expression::Assign { .. } => ASSIGNMENT,
+            Expression::Ternary { .. } => TERNARY,
+            Expression::Binary {
+                expression_type, ..
+            } => expression_type.precedence(),
+            Expression::Unary {
+                expression_type, ..
+            } => match expression_type {
+                UnaryExpressionType::Increment | UnaryExpressionType::Decrement => ADDITIVE,
+                UnaryExpressionType::Not
+                | UnaryExpressionType::Negate
+                | UnaryExpressionType::TypeOf
+                | UnaryExpressionType::Delete
+                | UnaryExpressionType::PreIncrement
+                | UnaryExpressionType::PreDecrement => PREFIX,
+                UnaryExpressionType::PostIncrement | UnaryExpressionType::PostDecrement => POSTFIX,
+                UnaryExpressionType::ToInteger
+                | UnaryExpressionType::ToString
+                | UnaryExpressionType::ToNumber => MEMBER,
+            },
+            // `5.x` doesn't parse and `-5.x` means something else
+            Expression::Literal(Variant::Int(_) | Variant::Float(_) | Variant::Double(_)) => PREFIX,
+            Expression::Reference(reference) => reference.precedence(),
+            Expression::Literal(_) => PRIMARY,
+            Expression::GetMember { .. }
+            | Expression::GetProperty { .. }
+            | Expression::CallFunction { .. }
+            | Expression::CallMethod { .. }
+            | Expression::New { .. } => MEMBER,
+        }
+    }
+
+    /// Prints the expression, wrapped in parentheses if it binds
+    /// less tightly than `precedence`
+    pub fn at(&self, precedence: u8) -> WithPrecedence<'_, Expression> {
+        WithPrecedence {
+            value: self,
+            precedence,
+        }
+    }
+}
+
+impl ReferenceExpression {
+    pub fn precedence(&self) -> u8 {
+        match self {
+            ReferenceExpression::Expression(expression) => expression.precedence(),
+            _ => PRIMARY,
+        }
+    }
+
+    pub fn at(&self, precedence: u8) -> WithPrecedence<'_, ReferenceExpression> {
+        WithPrecedence {
+            value: self,
+            precedence,
+        }
+    }
+}
+
+pub struct WithPrecedence<'a, T> {
+    value: &'a T,
+    precedence: u8,
+}
+
+impl Display for WithPrecedence<'_, Expression> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if self.value.precedence() < self.precedence {
+            write!(f, "({})", self.value)
+        } else {
+            write!(f, "{}", self.value)
+        }
+    }
+}
+
+impl Display for WithPrecedence<'_, ReferenceExpression> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self.value {
+            ReferenceExpression::Expression(expression) => {
+                write!(f, "{}", expression.at(self.precedence))
+            }
+            reference => write!(f, "{}", reference),
+        }
+    }
+}
@@ -0,0 +1,78 @@
+use crate::ast::expr::Expression;
+use crate::ast::variant::Variant;
+use std::fmt::{Display, Formatter};
+
+/// MovieClip properties in the order `GetProperty` and `SetProperty` index them
+const PROPERTIES: [&str; 22] = [
+    "_x",
+    "_y",
+    "_xscale",
+    "_yscale",
+    "_currentframe",
+    "_totalframes",
+    "_alpha",
+    "_visible",
+    "_width",
+    "_height",
+    "_rotation",
+    "_target",
+    "_framesloaded",
+    "_name",
+    "_droptarget",
+    "_url",
+    "_highquality",
+    "_focusrect",
+    "_soundbuftime",
+    "_quality",
+    "_xmouse",
+    "_ymouse",
+];
+
+#[derive(Debug, Clone)]
+pub enum Property {
+    Named(&'static str),
+    /// An index that is either not constant or out of range
+    Index(Box<Expression>),
+}
+
+impl Property {
+    pub fn from_index(index: Expression) -> Property {
+        let value = match &index {
+            Expression::Literal(Variant::Int(value)) => Some(*value as f64),
+            Expression::Literal(Variant::Float(value)) => Some(*value as f64),
+            Expression::Literal(Variant::Double(value)) => Some(*value),
+            Expression::Literal(Variant::String(value)) => value.parse::<f64>().ok(),
+            _ => None,
+        };
+        match value {
+            Some(value) if value >= 0.0 && value.fract() == 0.0 => PROPERTIES
+                .get(value as usize)
+                .map(|name| Property::Named(name))
+                .unwrap_or(Property::Index(Box::new(index))),
+            _ => Property::Index(Box::new(index)),
+        }
+    }
+
+    /// Prints just `_x` for the current timeline. Targets that are known to be
+    /// MovieClips already become member accesses while decompiling, anything
+    /// else could be a path string and has to stay `getProperty(path, _x)`.
+    pub fn as_member_of(&self, path: &Expression) -> Option<String> {
+        match (self, path) {
+            (Property::Named(name), Expression::Literal(Variant::String(path)))
+                if path.is_empty() =>
+            {
+                Some(name.to_string())
+            }
+            _ => None,
+        }
+    }
+}
+
+impl Display for Property {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Property::Named(name) => write!(f, "{}", name),
+            Property::Index(index) => write!(f, "{}", index),
+        }
+    }
+}
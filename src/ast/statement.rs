@@ -1,5 +1,9 @@
 use crate::ast::block::Block;
-use crate::ast::expr::{Expression, ReferenceExpression};
+use crate::ast::expr::{Expression, Member, ReferenceExpression};
+use crate::ast::identifier::{is_valid_variable_name, SetName};
+use crate::ast::literal::quote_string;
+use crate::ast::property::Property;
+use itertools::Itertools;
 use std::fmt::{Display, Formatter};
 
 #[derive(Debug, Clone)]
@@ -20,6 +24,11 @@ pub enum Statement {
         left: ReferenceExpression,
         right: Box<Expression>,
     },
+    SetProperty {
+        path: Expression,
+        property: Property,
+        value: Expression,
+    },
     If {
         condition: Expression,
         true_branch: Option<Block>,
@@ -35,33 +44,174 @@ pub enum Statement {
         increment: Box<Statement>,
         block: Block,
     },
+    /// `WaitForFrame`, frame numbers are printed 1-based
+    IfFrameLoaded {
+        frame: Expression,
+        block: Block,
+    },
+    TellTarget {
+        target: Expression,
+        block: Block,
+    },
+    /// `ActionImplementsOp`, only used for type checks so it has no source equivalent
+    Implements {
+        constructor: Expression,
+        interfaces: Vec<Expression>,
+    },
     Trace(Expression),
     Return(Option<Expression>),
     UnknownStatement(String),
     ExpressionStatement(Expression),
     DanglingStack(Expression),
-    GotoLabel(String),
-    GotoFrame(u16),
+    GotoLabel {
+        label: String,
+        play: bool,
+    },
+    /// `ActionGotoFrame`, `frame` is 0-based
+    GotoFrame {
+        frame: u16,
+        play: bool,
+    },
+    /// `ActionGotoFrame2`, `scene_bias` is added to numeric frames
+    GotoFrame2 {
+        frame: Expression,
+        play: bool,
+        scene_bias: u16,
+    },
     Play,
     Stop,
+    NextFrame,
+    PrevFrame,
+    DuplicateMovieClip {
+        source: Expression,
+        target: Expression,
+        depth: Expression,
+    },
+    RemoveMovieClip(Expression),
+    StartDrag {
+        target: Expression,
+        lock: Expression,
+        /// `left`, `top`, `right` and `bottom`
+        constraint: Option<Box<[Expression; 4]>>,
+    },
+    StopDrag,
+    StopAllSounds,
+    ToggleHighQuality,
     Pop(Expression),
     DecompileError(String),
 }
 
+impl Statement {
+    /// The value assigned by a `SetVariable` or `SetMember` statement
+    pub fn assigned_value(&self) -> Option<&Expression> {
+        match self {
+            Statement::SetVariable { right, .. } => Some(right),
+            Statement::SetMember { value, .. } => Some(value),
+            _ => None,
+        }
+    }
+
+    /// The target of a `SetVariable` or `SetMember` statement
+    pub fn assignment_target(&self) -> Option<Expression> {
+        match self {
+            Statement::SetVariable { left, .. } => Some(Expression::Reference(left.clone())),
+            Statement::SetMember { object, name, .. } => Some(Expression::GetMember {
+                object: object.clone(),
+                name: name.clone(),
+            }),
+            _ => None,
+        }
+    }
+
+    /// Turns a `SetVariable` or `SetMember` statement into an
+    /// assignment that can be used inside of other expressions
+    pub fn into_assign_expression(self) -> Option<Expression> {
+        match self {
+            Statement::SetVariable { left, right } => Some(Expression::Assign {
+                target: Box::new(Expression::Reference(left)),
+                operator: None,
+                value: right,
+            }),
+            Statement::SetMember {
+                object,
+                name,
+                value,
+            } => Some(Expression::Assign {
+                target: Box::new(Expression::GetMember { object, name }),
+                operator: None,
+                value: Box::new(value),
+            }),
+            _ => None,
+        }
+    }
+}
+
 impl Display for Statement {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         match self {
             Statement::DecompileError(error) => write!(f, "/* Decompile Error: {} */", error),
+            // `var` needs an identifier, `set` is the closest for other names
+            Statement::DefineLocal {
+                left: ReferenceExpression::Identifier(name),
+                right,
+            } if !is_valid_variable_name(name) => write!(f, "{}", SetName(name, right)),
             Statement::DefineLocal { left, right } => write!(f, "var {} = {}", left, right),
+            Statement::DeclareLocal {
+                name: ReferenceExpression::Identifier(name),
+            } if !is_valid_variable_name(name) => write!(f, "// var {}", quote_string(name)),
             Statement::DeclareLocal { name } => write!(f, "var {}", name),
             Statement::Trace(expr) => write!(f, "trace({})", expr),
             Statement::Play => write!(f, "play()"),
             Statement::Stop => write!(f, "stop()"),
-            Statement::GotoLabel(label) => {
-                write!(f, "gotoAndPlay(\"{}\")", label.replace('"', "\\\""))
+            Statement::GotoLabel { label, play } => {
+                write!(f, "{}({})", goto_function(*play), quote_string(label))
+            }
+            Statement::GotoFrame { frame, play } => {
+                write!(f, "{}({})", goto_function(*play), *frame as u32 + 1)
+            }
+            Statement::GotoFrame2 {
+                frame,
+                play,
+                scene_bias,
+            } => {
+                write!(f, "{}({})", goto_function(*play), frame)?;
+                if *scene_bias != 0 {
+                    write!(f, " // scene bias {}", scene_bias)?;
+                }
+                Ok(())
             }
-            Statement::GotoFrame(frame) => write!(f, "gotoAndPlay({})", frame),
+            Statement::NextFrame => write!(f, "nextFrame()"),
+            Statement::PrevFrame => write!(f, "prevFrame()"),
+            Statement::DuplicateMovieClip {
+                source,
+                target,
+                depth,
+            } => write!(f, "duplicateMovieClip({}, {}, {})", source, target, depth),
+            Statement::RemoveMovieClip(target) => write!(f, "removeMovieClip({})", target),
+            Statement::StartDrag {
+                target,
+                lock,
+                constraint,
+            } => match constraint {
+                Some(constraint) => write!(
+                    f,
+                    "startDrag({}, {}, {})",
+                    target,
+                    lock,
+                    constraint.iter().join(", ")
+                ),
+                None => write!(f, "startDrag({}, {})", target, lock),
+            },
+            Statement::StopDrag => write!(f, "stopDrag()"),
+            Statement::StopAllSounds => write!(f, "stopAllSounds()"),
+            Statement::ToggleHighQuality => write!(f, "toggleHighQuality()"),
             Statement::While { condition, block } => write!(f, "while ({}) {}", condition, block),
+            Statement::TellTarget { target, block } => {
+                write!(f, "tellTarget({}) {}", target, block)
+            }
+            Statement::IfFrameLoaded { frame, block } => {
+                write!(f, "ifFrameLoaded({}) {}", frame, block)
+            }
             Statement::For {
                 declare,
                 condition,
@@ -94,21 +244,29 @@ impl Display for Statement {
                 object,
                 name,
                 value,
-            } => match &name {
-                ReferenceExpression::Identifier(identifier) => {
-                    write!(f, "{}.{} = {}", object, identifier, value)
-                }
-                ReferenceExpression::Variable(var) => {
-                    write!(f, "{}[{}] = {}", object, var, value)
-                }
-                ReferenceExpression::Register(reg) => {
-                    write!(f, "{}[${}] = {}", object, reg, value)
-                }
-                ReferenceExpression::Expression(expr) => {
-                    write!(f, "{}[{}] = {}", object, expr, value)
-                }
-            },
+            } => write!(f, "{} = {}", Member(object, name), value),
+            Statement::SetVariable {
+                left: ReferenceExpression::Identifier(name),
+                right,
+            } => write!(f, "{}", SetName(name, right)),
             Statement::SetVariable { left, right } => write!(f, "{} = {}", left, right),
+            Statement::SetProperty {
+                path,
+                property,
+                value,
+            } => match property.as_member_of(path) {
+                Some(member) => write!(f, "{} = {}", member, value),
+                None => write!(f, "setProperty({}, {}, {})", path, property, value),
+            },
+            Statement::Implements {
+                constructor,
+                interfaces,
+            } => write!(
+                f,
+                "// {} implements {}",
+                constructor,
+                interfaces.iter().join(", ")
+            ),
             Statement::UnknownStatement(x) => write!(f, "// ??? {}", x),
             Statement::Return(value) => match value {
                 Some(value) => write!(f, "return {}", value),
@@ -124,3 +282,11 @@ impl Display for Statement {
         }
     }
 }
+
+fn goto_function(play: bool) -> &'static str {
+    if play {
+        "gotoAndPlay"
+    } else {
+        "gotoAndStop"
+    }
+}
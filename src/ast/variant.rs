@@ -1,4 +1,6 @@
 use crate::ast::expr::Expression;
+use crate::ast::identifier::{is_array_index, is_valid_identifier};
+use crate::ast::literal::{format_f32, format_f64, quote_string, quote_utf16};
 use std::fmt::{Display, Formatter};
 
 #[derive(Debug, Clone)]
@@ -11,6 +13,8 @@ pub enum Variant {
     Float(f32),
     Double(f64),
     String(String),
+    /// A string with lone surrogates, which a `String` can't hold
+    IllFormedString(Vec<u16>),
     Array(Vec<Expression>),
     Object(Vec<(Expression, Expression)>),
 }
@@ -23,18 +27,37 @@ impl Display for Variant {
             Variant::Null => write!(f, "null"),
             Variant::Bool(value) => write!(f, "{}", value),
             Variant::Int(value) => write!(f, "{}", value),
-            Variant::Float(value) => write!(f, "{:?}", value),
-            Variant::Double(value) => write!(f, "{:?}", value),
-            Variant::String(value) => write!(f, "\"{}\"", value.replace('"', "\\\"")),
+            Variant::Float(value) => write!(f, "{}", format_f32(*value)),
+            Variant::Double(value) => write!(f, "{}", format_f64(*value)),
+            Variant::String(value) => write!(f, "{}", quote_string(value)),
+            Variant::IllFormedString(units) => write!(f, "{}", quote_utf16(units)),
             Variant::Array(value) => {
                 let members_fmt: Vec<String> = value.iter().map(|x| x.to_string()).collect();
                 write!(f, "[{}]", members_fmt.join(", "))
             }
             Variant::Object(value) => {
-                let members_fmt: Vec<String> =
-                    value.iter().map(|(a, b)| format!("{}: {}", a, b)).collect();
+                let members_fmt: Vec<String> = value
+                    .iter()
+                    .map(|(key, value)| format!("{}: {}", ObjectKey(key), value))
+                    .collect();
                 write!(f, "{{{}}}", members_fmt.join(", "))
             }
         }
     }
 }
+
+/// Prints string keys without quotes where possible
+struct ObjectKey<'a>(&'a Expression);
+
+impl Display for ObjectKey<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self.0 {
+            Expression::Literal(Variant::String(key))
+                if is_valid_identifier(key) || is_array_index(key) =>
+            {
+                write!(f, "{}", key)
+            }
+            key => write!(f, "{}", key),
+        }
+    }
+}
@@ -0,0 +1,241 @@
+use crate::ast::expr::{Expression, ReferenceExpression};
+use crate::ast::property::Property;
+use crate::ast::statement::Statement;
+use crate::ast::variant::Variant;
+
+/// Mutable AST traversal used by the passes that run after
+/// the single sweep. Override the methods you are interested in
+/// and call the matching `walk_*` function to keep descending.
+pub trait VisitMut {
+    fn visit_statements(&mut self, statements: &mut Vec<Statement>) {
+        walk_statements(self, statements)
+    }
+
+    fn visit_statement(&mut self, statement: &mut Statement) {
+        walk_statement(self, statement)
+    }
+
+    fn visit_expression(&mut self, expression: &mut Expression) {
+        walk_expression(self, expression)
+    }
+
+    fn visit_reference(&mut self, reference: &mut ReferenceExpression) {
+        walk_reference(self, reference)
+    }
+
+    /// The name in `object.name`, `object.name()` and `object.name = value`
+    fn visit_member_name(&mut self, name: &mut ReferenceExpression) {
+        self.visit_reference(name)
+    }
+}
+
+pub fn walk_statements<V: VisitMut + ?Sized>(visitor: &mut V, statements: &mut [Statement]) {
+    for statement in statements.iter_mut() {
+        visitor.visit_statement(statement)
+    }
+}
+
+pub fn walk_statement<V: VisitMut + ?Sized>(visitor: &mut V, statement: &mut Statement) {
+    match statement {
+        Statement::DefineLocal { left, right } => {
+            visitor.visit_reference(left);
+            visitor.visit_expression(right);
+        }
+        Statement::DeclareLocal { name } => visitor.visit_reference(name),
+        Statement::SetMember {
+            object,
+            name,
+            value,
+        } => {
+            visitor.visit_reference(object);
+            visitor.visit_member_name(name);
+            visitor.visit_expression(value);
+        }
+        Statement::SetVariable { left, right } => {
+            visitor.visit_reference(left);
+            visitor.visit_expression(right);
+        }
+        Statement::SetProperty {
+            path,
+            property,
+            value,
+        } => {
+            visitor.visit_expression(path);
+            if let Property::Index(index) = property {
+                visitor.visit_expression(index);
+            }
+            visitor.visit_expression(value);
+        }
+        Statement::If {
+            condition,
+            true_branch,
+            false_branch,
+        } => {
+            visitor.visit_expression(condition);
+            if let Some(block) = true_branch {
+                visitor.visit_statements(&mut block.body);
+            }
+            if let Some(block) = false_branch {
+                visitor.visit_statements(&mut block.body);
+            }
+        }
+        Statement::IfFrameLoaded {
+            frame: target,
+            block,
+        }
+        | Statement::TellTarget { target, block } => {
+            visitor.visit_expression(target);
+            visitor.visit_statements(&mut block.body);
+        }
+        Statement::While { condition, block } => {
+            visitor.visit_expression(condition);
+            visitor.visit_statements(&mut block.body);
+        }
+        Statement::For {
+            declare,
+            condition,
+            increment,
+            block,
+        } => {
+            visitor.visit_statement(declare);
+            visitor.visit_expression(condition);
+            visitor.visit_statement(increment);
+            visitor.visit_statements(&mut block.body);
+        }
+        Statement::GotoFrame2 { frame, .. } => visitor.visit_expression(frame),
+        Statement::DuplicateMovieClip {
+            source,
+            target,
+            depth,
+        } => {
+            visitor.visit_expression(source);
+            visitor.visit_expression(target);
+            visitor.visit_expression(depth);
+        }
+        Statement::StartDrag {
+            target,
+            lock,
+            constraint,
+        } => {
+            visitor.visit_expression(target);
+            visitor.visit_expression(lock);
+            if let Some(constraint) = constraint {
+                for expression in constraint.iter_mut() {
+                    visitor.visit_expression(expression);
+                }
+            }
+        }
+        Statement::Implements {
+            constructor,
+            interfaces,
+        } => {
+            visitor.visit_expression(constructor);
+            for interface in interfaces.iter_mut() {
+                visitor.visit_expression(interface);
+            }
+        }
+        Statement::Return(Some(expression))
+        | Statement::RemoveMovieClip(expression)
+        | Statement::Trace(expression)
+        | Statement::ExpressionStatement(expression)
+        | Statement::DanglingStack(expression)
+        | Statement::Pop(expression) => visitor.visit_expression(expression),
+        Statement::Return(None)
+        | Statement::UnknownStatement(_)
+        | Statement::GotoLabel { .. }
+        | Statement::GotoFrame { .. }
+        | Statement::Play
+        | Statement::Stop
+        | Statement::NextFrame
+        | Statement::PrevFrame
+        | Statement::StopDrag
+        | Statement::StopAllSounds
+        | Statement::ToggleHighQuality
+        | Statement::DecompileError(_) => {}
+    }
+}
+
+pub fn walk_expression<V: VisitMut + ?Sized>(visitor: &mut V, expression: &mut Expression) {
+    match expression {
+        Expression::Superposition(superpositions) => {
+            for superposition in superpositions.iter_mut() {
+                visitor.visit_expression(&mut superposition.value);
+            }
+        }
+        Expression::Reference(reference) => visitor.visit_reference(reference),
+        Expression::Function {
+            parameters, body, ..
+        } => {
+            for parameter in parameters.iter_mut() {
+                visitor.visit_reference(parameter);
+            }
+            visitor.visit_statements(&mut body.body);
+        }
+        Expression::GetMember { object, name } => {
+            visitor.visit_reference(object);
+            visitor.visit_member_name(name);
+        }
+        Expression::GetProperty { path, property } => {
+            visitor.visit_expression(path);
+            if let Property::Index(index) = property {
+                visitor.visit_expression(index);
+            }
+        }
+        Expression::Ternary {
+            condition,
+            if_true,
+            if_false,
+        } => {
+            visitor.visit_expression(condition);
+            visitor.visit_expression(if_true);
+            visitor.visit_expression(if_false);
+        }
+        Expression::Binary { left, right, .. } => {
+            visitor.visit_expression(left);
+            visitor.visit_expression(right);
+        }
+        Expression::Unary { target, .. } => visitor.visit_expression(target),
+        Expression::Literal(Variant::Array(elements)) => {
+            for element in elements.iter_mut() {
+                visitor.visit_expression(element);
+            }
+        }
+        Expression::Literal(Variant::Object(properties)) => {
+            for (key, value) in properties.iter_mut() {
+                visitor.visit_expression(key);
+                visitor.visit_expression(value);
+            }
+        }
+        Expression::Literal(_) => {}
+        Expression::CallFunction { name, args } => {
+            visitor.visit_reference(name);
+            for arg in args.iter_mut() {
+                visitor.visit_expression(arg);
+            }
+        }
+        Expression::CallMethod { object, name, args } => {
+            visitor.visit_reference(object);
+            visitor.visit_member_name(name);
+            for arg in args.iter_mut() {
+                visitor.visit_expression(arg);
+            }
+        }
+        Expression::New { constructor, args } => {
+            visitor.visit_expression(constructor);
+            for arg in args.iter_mut() {
+                visitor.visit_expression(arg);
+            }
+        }
+        Expression::StoreRegister { value, .. } => visitor.visit_expression(value),
+        Expression::Assign { target, value, .. } => {
+            visitor.visit_expression(target);
+            visitor.visit_expression(value);
+        }
+    }
+}
+
+pub fn walk_reference<V: VisitMut + ?Sized>(visitor: &mut V, reference: &mut ReferenceExpression) {
+    if let ReferenceExpression::Expression(expression) = reference {
+        visitor.visit_expression(expression)
+    }
+}
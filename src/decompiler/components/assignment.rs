@@ -0,0 +1,59 @@
+use crate::ast::expr::{Expression, ReferenceExpression};
+use crate::ast::statement::Statement;
+use crate::decompiler::passes::compound_assignment::as_update;
+use crate::decompiler::vm::VirtualMachine;
+use swf::error::Result;
+
+/// When the result of an assignment is used again, like in
+/// `a = b = 0` or `if ((x = next()) != null)`, compilers keep the
+/// assigned value in a register and read it right after the assignment.
+///
+/// In that case the assignment is pulled back into the expression.
+pub fn decompile_register_read(vm: &mut VirtualMachine, register: u8) -> Expression {
+    if let Some(name) = vm.register_name(register) {
+        return Expression::Reference(ReferenceExpression::Identifier(name.to_string()));
+    }
+    // a branch that skips the store reads whatever was in the register before
+    if vm.is_jump_target() {
+        return Expression::Reference(ReferenceExpression::Register(register));
+    }
+    vm.take_last_statement(|statement| {
+        matches!(
+            statement.assigned_value(),
+            Some(Expression::StoreRegister { id, .. }) if *id == register
+        )
+    })
+    .and_then(Statement::into_assign_expression)
+    .unwrap_or(Expression::Reference(ReferenceExpression::Register(
+        register,
+    )))
+}
+
+/// Post-increments like `y = x++` keep a copy of the old value on the
+/// stack (through PushDuplicate) and then store the incremented value,
+/// so if the copy is still on top of the stack it becomes `x++` instead.
+pub fn append_assignment(vm: &mut VirtualMachine, statement: Statement) -> Result<()> {
+    let target = match &statement {
+        Statement::ExpressionStatement(Expression::StoreRegister { id, value }) => Some((
+            Expression::Reference(ReferenceExpression::Register(*id)),
+            value.as_ref(),
+        )),
+        _ => statement
+            .assignment_target()
+            .zip(statement.assigned_value()),
+    };
+    if let Some((target, value)) = target {
+        if let Some(expression_type) = as_update(&target, value, false) {
+            if vm.peek().is_some_and(|it| it.is_same_target(&target)) {
+                vm.pop()?;
+                vm.push(Expression::Unary {
+                    target: Box::new(target),
+                    expression_type,
+                });
+                return Ok(());
+            }
+        }
+    }
+    vm.append_statement(statement);
+    Ok(())
+}
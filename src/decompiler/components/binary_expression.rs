@@ -1,5 +1,6 @@
 use crate::ast::binary_expr::BinaryExpressionType;
-use crate::ast::expr::Expression;
+use crate::ast::expr::{Expression, UnaryExpressionType};
+use crate::ast::variant::Variant;
 use crate::decompiler::vm::VirtualMachine;
 use swf::error::Result;
 
@@ -9,10 +10,32 @@ pub fn decompile_binary_expr(
 ) -> Result<()> {
     let right = vm.pop()?;
     let left = vm.pop()?;
-    vm.push(Expression::Binary {
-        left: Box::new(left),
-        right: Box::new(right),
-        expression_type,
+    vm.push(match (expression_type, left, right) {
+        // compilers emit `-x` as `0 - x` or `-1 * x`
+        (BinaryExpressionType::Subtract, left, right) if is_literal(&left, 0.0) => negate(right),
+        (BinaryExpressionType::Multiply, left, right) if is_literal(&left, -1.0) => negate(right),
+        (BinaryExpressionType::Multiply, left, right) if is_literal(&right, -1.0) => negate(left),
+        (expression_type, left, right) => Expression::Binary {
+            left: Box::new(left),
+            right: Box::new(right),
+            expression_type,
+        },
     });
     Ok(())
 }
+
+fn negate(expression: Expression) -> Expression {
+    Expression::Unary {
+        target: Box::new(expression),
+        expression_type: UnaryExpressionType::Negate,
+    }
+}
+
+fn is_literal(expression: &Expression, expected: f64) -> bool {
+    match expression {
+        Expression::Literal(Variant::Int(value)) => *value as f64 == expected,
+        Expression::Literal(Variant::Float(value)) => *value as f64 == expected,
+        Expression::Literal(Variant::Double(value)) => *value == expected,
+        _ => false,
+    }
+}
@@ -1,40 +1,69 @@
 use crate::ast::block::Block;
 use crate::ast::expr::{Expression, ReferenceExpression};
-use crate::ast::variant::Variant;
+use crate::ast::identifier::is_valid_variable_name;
+use crate::decompiler::read::read;
 use crate::decompiler::vm::VirtualMachine;
-use crate::decompiler::{decompile, VmData};
-use std::num::NonZeroU8;
-use swf::avm1::types::DefineFunction2;
+use crate::decompiler::{decompile, Register, VmData};
+use swf::avm1::read::Reader;
+use swf::avm1::types::{Action, DefineFunction2, FunctionFlags};
 use swf::error::Result;
 use swf::UTF_8;
 
+const PRELOADED: [(FunctionFlags, &str); 6] = [
+    (FunctionFlags::PRELOAD_THIS, "this"),
+    (FunctionFlags::PRELOAD_ARGUMENTS, "arguments"),
+    (FunctionFlags::PRELOAD_SUPER, "super"),
+    (FunctionFlags::PRELOAD_ROOT, "_root"),
+    (FunctionFlags::PRELOAD_PARENT, "_parent"),
+    (FunctionFlags::PRELOAD_GLOBAL, "_global"),
+];
+
 pub fn decompile_define_function(
     vm: &mut VirtualMachine,
     function: DefineFunction2,
 ) -> Result<Expression> {
-    let mut registers: Vec<Expression> = (0..function.register_count)
-        .map(|_| Expression::Literal(Variant::Uninitialized))
-        .collect();
+    let mut registers = vec![Register::Unnamed; function.register_count as usize];
     let mut params = Vec::<ReferenceExpression>::with_capacity(function.params.len());
 
+    // preloaded values take the registers from 1 on, in this order.
+    // `this = value` isn't valid, so registers that are stored to anywhere
+    // stay `$n`, as which value a read sees depends on the control flow.
+    let stored = stored_registers(vm, function.actions);
+    let preloaded = PRELOADED
+        .iter()
+        .filter(|(flag, _)| function.flags.contains(*flag));
+    for ((_, name), register) in preloaded.zip(1..) {
+        if stored.as_ref().is_some_and(|it| !it.contains(&register)) {
+            name_register(&mut registers, register, Register::Preloaded(name));
+        }
+    }
+
     for param in function.params.into_iter() {
         let name = param.name.to_string_lossy(UTF_8);
-        let result = ReferenceExpression::Identifier(name);
-        let register_index = if let Some(index) = param.register_index {
-            index
-        } else {
-            NonZeroU8::new(1).unwrap()
-        };
-
-        params.push(result.clone());
-        registers[register_index.get() as usize] = Expression::Reference(result);
+        // parameters without a register are only in `arguments` and scope
+        match param.register_index {
+            // reads of the register can't print a name that isn't an identifier
+            Some(register) if !is_valid_variable_name(&name) => {
+                params.push(ReferenceExpression::Register(register.get()))
+            }
+            Some(register) => {
+                name_register(
+                    &mut registers,
+                    register.get(),
+                    Register::Parameter(name.clone()),
+                );
+                params.push(ReferenceExpression::Identifier(name));
+            }
+            None => params.push(ReferenceExpression::Identifier(name)),
+        }
     }
 
     let body = decompile(VmData {
         bytecode: function.actions,
         registers,
         constant_pool: vm.data.constant_pool,
-        strict: vm.data.strict,
+        options: vm.data.options,
+        opcodes: vm.data.opcodes,
     })?;
     let name = function.name.to_string_lossy(UTF_8);
     Ok(Expression::Function {
@@ -44,3 +73,34 @@ pub fn decompile_define_function(
         body: Block { body },
     })
 }
+
+/// The registers that `StoreRegister` actions in the body write to,
+/// without nested functions. `None` if the body can't be read.
+fn stored_registers(vm: &VirtualMachine, bytecode: &[u8]) -> Option<Vec<u8>> {
+    let mut reader = Reader::new(bytecode, 1);
+    let mut registers = Vec::new();
+    while !reader.get_ref().is_empty() {
+        match read(
+            &mut reader,
+            bytecode,
+            vm.data.constant_pool,
+            vm.data.opcodes,
+        )
+        .ok()?
+        {
+            Action::End => break,
+            Action::StoreRegister(store) => registers.push(store.register),
+            _ => {}
+        }
+    }
+    Some(registers)
+}
+
+/// Also names registers past `register_count`, which broken compilers produce
+fn name_register(registers: &mut Vec<Register>, register: u8, name: Register) {
+    let i = register as usize;
+    if registers.len() <= i {
+        registers.resize(i + 1, Register::Unnamed);
+    }
+    registers[i] = name;
+}
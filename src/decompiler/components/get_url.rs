@@ -0,0 +1,125 @@
+use crate::ast::binary_expr::BinaryExpressionType;
+use crate::ast::expr::{Expression, ReferenceExpression};
+use crate::ast::statement::Statement;
+use crate::ast::variant::Variant;
+use crate::decompiler::vm::VirtualMachine;
+use swf::avm1::types::{GetUrl2, SendVarsMethod};
+use swf::error::Result;
+
+const FS_COMMAND: &str = "FSCommand:";
+const LEVEL: &str = "_level";
+
+pub fn decompile_get_url2(vm: &mut VirtualMachine, flags: GetUrl2) -> Result<()> {
+    let target = vm.pop()?;
+    let url = vm.pop()?;
+    vm.append_statement(get_url_statement(
+        url,
+        target,
+        flags.is_target_sprite(),
+        flags.is_load_vars(),
+        flags.send_vars_method(),
+    ));
+    Ok(())
+}
+
+/// Picks the function that compiles to `GetURL`/`GetURL2` with these flags,
+/// e.g. `loadMovieNum` for a `_level` target or `unloadMovie` for an empty url
+pub fn get_url_statement(
+    url: Expression,
+    target: Expression,
+    load_target: bool,
+    load_vars: bool,
+    method: SendVarsMethod,
+) -> Statement {
+    if !load_target && !load_vars {
+        if let Some(command) = strip_string_prefix(&url, FS_COMMAND) {
+            return if is_empty_string(&target) {
+                call("fscommand", vec![command])
+            } else {
+                call("fscommand", vec![command, target])
+            };
+        }
+    }
+
+    let level = if load_target {
+        None
+    } else {
+        strip_string_prefix(&target, LEVEL)
+            .map(level_number)
+            .filter(|level| !matches!(level, Expression::Literal(Variant::String(_))))
+    };
+    let unload = is_empty_string(&url);
+    let method = match method {
+        SendVarsMethod::None => None,
+        SendVarsMethod::Get => Some(string("GET")),
+        SendVarsMethod::Post => Some(string("POST")),
+    };
+
+    let (name, args) = match (load_vars, load_target, level) {
+        (true, _, Some(level)) => ("loadVariablesNum", vec![url, level]),
+        (true, _, None) => ("loadVariables", vec![url, target]),
+        (false, true, _) if unload && method.is_none() => ("unloadMovie", vec![target]),
+        (false, true, _) => ("loadMovie", vec![url, target]),
+        (false, false, Some(level)) if unload && method.is_none() => {
+            ("unloadMovieNum", vec![level])
+        }
+        (false, false, Some(level)) => ("loadMovieNum", vec![url, level]),
+        (false, false, None) if is_empty_string(&target) && method.is_none() => {
+            ("getURL", vec![url])
+        }
+        (false, false, None) => ("getURL", vec![url, target]),
+    };
+    call(name, args.into_iter().chain(method).collect())
+}
+
+fn call(name: &str, args: Vec<Expression>) -> Statement {
+    Statement::ExpressionStatement(Expression::CallFunction {
+        name: ReferenceExpression::Identifier(name.to_string()),
+        args,
+    })
+}
+
+fn string(value: &str) -> Expression {
+    Expression::Literal(Variant::String(value.to_string()))
+}
+
+fn is_empty_string(expression: &Expression) -> bool {
+    matches!(expression, Expression::Literal(Variant::String(it)) if it.is_empty())
+}
+
+/// Removes `prefix` from a string literal or from the left side
+/// of the concatenation the compiler emits for non-constant values
+fn strip_string_prefix(expression: &Expression, prefix: &str) -> Option<Expression> {
+    match expression {
+        Expression::Literal(Variant::String(value)) => value
+            .strip_prefix(prefix)
+            .map(|rest| Expression::Literal(Variant::String(rest.to_string()))),
+        Expression::Binary {
+            left,
+            right,
+            expression_type:
+                BinaryExpressionType::Add
+                | BinaryExpressionType::NumericAdd
+                | BinaryExpressionType::StringAdd,
+        } => match left.as_ref() {
+            Expression::Literal(Variant::String(value)) if value == prefix => {
+                Some(right.as_ref().clone())
+            }
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// The level of a `_levelN` target, stays a string if `N` isn't a number
+fn level_number(level: Expression) -> Expression {
+    match level {
+        Expression::Literal(Variant::String(value)) => match value.parse::<i32>() {
+            Ok(number) if number >= 0 && number.to_string() == value => {
+                Expression::Literal(Variant::Int(number))
+            }
+            _ => Expression::Literal(Variant::String(value)),
+        },
+        level => level,
+    }
+}
@@ -1,4 +1,8 @@
+pub mod assignment;
 pub mod binary_expression;
 pub mod functions;
-pub mod unary_expression;
+pub mod get_url;
 pub mod jump_logger;
+pub mod target_path;
+pub mod unary_expression;
+pub mod variables;
@@ -0,0 +1,102 @@
+use crate::ast::expr::{Expression, ReferenceExpression};
+use crate::ast::identifier::{is_valid_identifier, is_valid_variable_name};
+use crate::ast::variant::Variant;
+
+/// Turns SWF4 slash syntax paths like `/menu/item:count` or `../:x`
+/// into member expressions like `_root.menu.item.count` and `_parent.x`.
+/// Returns `None` for plain names and for anything that can't be written with dots.
+pub fn parse_target_path(path: &str) -> Option<Expression> {
+    if !path.contains(['/', ':']) {
+        return None;
+    }
+
+    let (clip, variable) = match path.rsplit_once(':') {
+        Some((clip, variable)) => (clip, Some(variable)),
+        None => (path, None),
+    };
+    let (root, clip) = match clip.strip_prefix('/') {
+        Some(clip) => (Some("_root"), clip),
+        None => (None, clip),
+    };
+
+    let names = root
+        .into_iter()
+        .chain(
+            clip.split('/')
+                .filter(|name| !name.is_empty() && *name != ".")
+                .map(|name| if name == ".." { "_parent" } else { name }),
+        )
+        .chain(variable);
+
+    let mut expression: Option<Expression> = None;
+    for name in names {
+        expression = Some(match expression {
+            None if is_valid_variable_name(name) => {
+                Expression::Reference(ReferenceExpression::Identifier(name.to_string()))
+            }
+            Some(object) if is_valid_identifier(name) => Expression::GetMember {
+                object: ReferenceExpression::from_expression(object),
+                name: ReferenceExpression::Identifier(name.to_string()),
+            },
+            _ => return None,
+        });
+    }
+    expression
+}
+
+/// Turns a dot path like `menu.item.count` into a member chain,
+/// and a plain name into a variable
+pub fn parse_dot_path(path: &str) -> Option<Expression> {
+    let mut names = path.split('.');
+    let first = names.next().filter(|name| is_valid_variable_name(name))?;
+    let mut expression = Expression::Reference(ReferenceExpression::Variable(first.to_string()));
+    for name in names {
+        if !is_valid_identifier(name) {
+            return None;
+        }
+        expression = Expression::GetMember {
+            object: ReferenceExpression::from_expression(expression),
+            name: ReferenceExpression::Identifier(name.to_string()),
+        };
+    }
+    Some(expression)
+}
+
+/// The MovieClip that the target of `GetProperty` or `SetProperty` refers to,
+/// for slash syntax paths and for member chains starting at `this`, `_root`,
+/// `_parent` or `_levelN`. Other targets could hold a path string.
+pub fn resolve_clip(path: &Expression, keep_slash_syntax: bool) -> Option<Expression> {
+    match path {
+        // a path with a `:` names a variable, not a clip
+        Expression::Literal(Variant::String(path)) if !keep_slash_syntax && !path.contains(':') => {
+            parse_target_path(path)
+        }
+        Expression::Literal(_) => None,
+        path if is_clip_reference(path) => Some(path.clone()),
+        _ => None,
+    }
+}
+
+fn is_clip_reference(expression: &Expression) -> bool {
+    match expression {
+        Expression::Reference(reference) => is_clip_reference_name(reference),
+        Expression::GetMember {
+            object,
+            name: ReferenceExpression::Identifier(_),
+        } => is_clip_reference_name(object),
+        _ => false,
+    }
+}
+
+fn is_clip_reference_name(reference: &ReferenceExpression) -> bool {
+    match reference {
+        ReferenceExpression::Identifier(name) | ReferenceExpression::Variable(name) => {
+            matches!(name.as_str(), "this" | "_root" | "_parent")
+                || name.strip_prefix("_level").is_some_and(|level| {
+                    !level.is_empty() && level.chars().all(|it| it.is_ascii_digit())
+                })
+        }
+        ReferenceExpression::Expression(expression) => is_clip_reference(expression),
+        ReferenceExpression::Register(_) => false,
+    }
+}
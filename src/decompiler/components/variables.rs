@@ -0,0 +1,112 @@
+use crate::ast::binary_expr::BinaryExpressionType;
+use crate::ast::expr::{Expression, ReferenceExpression};
+use crate::ast::statement::Statement;
+use crate::ast::variant::Variant;
+use crate::decompiler::components::assignment::append_assignment;
+use crate::decompiler::components::target_path::{parse_dot_path, parse_target_path};
+use crate::decompiler::vm::VirtualMachine;
+use swf::error::Result;
+
+pub fn decompile_get_variable(vm: &mut VirtualMachine) -> Result<()> {
+    let name = vm.pop()?;
+    let variable = match resolve_variable(vm, &name) {
+        Some(variable) => variable,
+        None => call("eval", vec![constant_name(name)]),
+    };
+    vm.push(variable);
+    Ok(())
+}
+
+pub fn decompile_set_variable(vm: &mut VirtualMachine) -> Result<()> {
+    let value = vm.pop()?;
+    let name = vm.pop()?;
+    match resolve_variable(vm, &name) {
+        Some(Expression::GetMember { object, name }) => append_assignment(
+            vm,
+            Statement::SetMember {
+                object,
+                name,
+                value,
+            },
+        ),
+        Some(Expression::Reference(left)) => append_assignment(
+            vm,
+            Statement::SetVariable {
+                left,
+                right: value.into(),
+            },
+        ),
+        _ => {
+            let statement = call("set", vec![constant_name(name), value]);
+            vm.append_statement(Statement::ExpressionStatement(statement));
+            Ok(())
+        }
+    }
+}
+
+/// The variable or member chain a name refers to, if it is constant
+/// and can be written as a path. Everything else needs `eval` or `set`.
+fn resolve_variable(vm: &VirtualMachine, name: &Expression) -> Option<Expression> {
+    let name = fold_string(name)?;
+    if name.contains(['/', ':']) {
+        if vm.data.options.keep_slash_syntax {
+            Some(Expression::Reference(ReferenceExpression::Variable(name)))
+        } else {
+            parse_target_path(&name)
+        }
+    } else {
+        parse_dot_path(&name)
+    }
+}
+
+/// Replaces names that fold to a constant with that constant
+fn constant_name(name: Expression) -> Expression {
+    match fold_string(&name) {
+        Some(folded) => Expression::Literal(Variant::String(folded)),
+        None => name,
+    }
+}
+
+/// Evaluates string concatenations of constants, like `"item" + 1`
+fn fold_string(expression: &Expression) -> Option<String> {
+    match expression {
+        Expression::Literal(Variant::String(value)) => Some(value.clone()),
+        Expression::Binary {
+            left,
+            right,
+            expression_type: BinaryExpressionType::StringAdd,
+        } => Some(format!("{}{}", fold_constant(left)?, fold_constant(right)?)),
+        // `+` only concatenates if one of the operands is a string
+        Expression::Binary {
+            left,
+            right,
+            expression_type: BinaryExpressionType::Add,
+        } if is_string(left) || is_string(right) => {
+            Some(format!("{}{}", fold_constant(left)?, fold_constant(right)?))
+        }
+        _ => None,
+    }
+}
+
+fn is_string(expression: &Expression) -> bool {
+    fold_string(expression).is_some()
+}
+
+fn fold_constant(expression: &Expression) -> Option<String> {
+    match expression {
+        Expression::Literal(Variant::Int(value)) => Some(value.to_string()),
+        Expression::Literal(Variant::Double(value))
+            if value.fract() == 0.0 && value.abs() < 1e15 =>
+        {
+            Some((*value as i64).to_string())
+        }
+        expression => fold_string(expression),
+    }
+}
+
+fn call(name: &str, args: Vec<Expression>) -> Expression {
+    Expression::CallFunction {
+        name: ReferenceExpression::Identifier(name.to_string()),
+        args,
+    }
+}
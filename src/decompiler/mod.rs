@@ -1,42 +1,97 @@
 mod components;
+pub mod opcodes;
+mod passes;
+pub mod pool;
 mod read;
+pub mod rename;
 mod vm;
 use crate::ast::binary_expr::BinaryExpressionType;
-use crate::ast::block::Block;
 use crate::ast::expr::{Expression, ReferenceExpression, UnaryExpressionType};
+use crate::ast::property::Property;
 use crate::ast::statement::Statement;
 use crate::ast::variant::Variant;
+use crate::decompiler::components::assignment::{append_assignment, decompile_register_read};
 use crate::decompiler::components::binary_expression::decompile_binary_expr;
 use crate::decompiler::components::functions::decompile_define_function;
+use crate::decompiler::components::get_url::{decompile_get_url2, get_url_statement};
+use crate::decompiler::components::target_path::resolve_clip;
 use crate::decompiler::components::unary_expression::decompile_unary_expr;
+use crate::decompiler::components::variables::{decompile_get_variable, decompile_set_variable};
+use crate::decompiler::opcodes::{OpcodeDecoder, Pops};
+use crate::decompiler::passes::simplify::negate;
+use crate::decompiler::pool::{string_variant, PoolString};
+use crate::decompiler::read::FS_COMMAND_2;
 use crate::decompiler::vm::VirtualMachine;
 use itertools::Itertools;
 use std::borrow::Cow;
-use swf::avm1::types::{Action, Value};
+use swf::avm1::types::{Action, SendVarsMethod, Unknown, Value};
 use swf::error::{Error, Result};
 use swf::UTF_8;
 
+#[derive(Debug, Default, Clone, Copy)]
+pub struct DecompileOptions {
+    /// Reject when occurring parse errors
+    pub strict: bool,
+    /// Fold `!(a < b)` into `a >= b` even when an operand could be `NaN`
+    pub assume_no_nan: bool,
+    /// Print SWF4 paths like `/menu:count` as they are instead of `_root.menu.count`
+    pub keep_slash_syntax: bool,
+    /// Leave constant expressions and identities like `x * 1` as they are
+    pub no_simplify: bool,
+}
+
+/// What the name of a register is when it's read
+#[derive(Debug, Default, Clone)]
+pub enum Register {
+    /// Printed as `$n`
+    #[default]
+    Unnamed,
+    /// Storing to it assigns the parameter
+    Parameter(String),
+    /// `this`, `_root` and the like, if the function never stores to the register
+    Preloaded(&'static str),
+}
+
 #[derive(Debug, Default)]
 pub struct VmData<'a> {
     pub bytecode: &'a [u8],
-    pub constant_pool: &'a [String],
-    pub strict: bool,
-    pub registers: Vec<Expression>,
+    pub constant_pool: &'a [PoolString],
+    pub options: DecompileOptions,
+    pub registers: Vec<Register>,
+    /// Proprietary opcodes, if the player deviates from the standard.
+    /// The bytecode is expected to be passed through `remap_opcodes` already.
+    pub opcodes: Option<&'a dyn OpcodeDecoder>,
 }
 
 pub fn decompile(data: VmData) -> Result<Vec<Statement>> {
     let mut vm: VirtualMachine = data.into();
 
     loop {
-        match vm.read_action()? {
-            Action::End => return Ok(vm.finalize()),
-            action => {
+        match vm.read_action() {
+            Ok(Action::End) => return Ok(vm.finalize()),
+            Ok(action) => {
                 if let Err(error) = decompile_action(&mut vm, action) {
                     vm.append_statement(Statement::DecompileError(error.to_string()));
                     eprintln!("Decompile Error: {}", error);
                 }
             }
+            Err(error) if vm.data.options.strict => return Err(error),
+            Err(error) => {
+                eprintln!("Malformed action at offset {}: {}", vm.offset(), error);
+                match vm.skip_action() {
+                    Some(unknown) => vm.append_statement(Statement::UnknownStatement(format!(
+                        "malformed {} ({})",
+                        describe_unknown(&unknown),
+                        error
+                    ))),
+                    None => {
+                        vm.append_statement(Statement::DecompileError(error.to_string()));
+                        return Ok(vm.finalize());
+                    }
+                }
+            }
         }
+        vm.end_action();
     }
 }
 
@@ -66,27 +121,40 @@ fn decompile_action(vm: &mut VirtualMachine, action: Action) -> Result<()> {
         }
         Action::CallFunction => {
             let name = ReferenceExpression::from_expression(vm.pop()?);
-            let num_args = match vm.pop()? {
-                Expression::Literal(Variant::Int(i)) => Ok(i as usize),
-                _ => Err(Error::invalid_data(
-                    "Tried calling a function with non-constant arg count",
-                )),
-            }?;
-            let args = vm.pop_len(num_args)?;
+            let args = vm.pop_args()?;
             vm.push(Expression::CallFunction { name, args })
         }
         Action::CallMethod => {
             let name = ReferenceExpression::from_expression(vm.pop()?);
             let object = ReferenceExpression::from_expression(vm.pop()?);
-            let num_args = match vm.pop()? {
-                Expression::Literal(Variant::Int(i)) => Ok(i as usize),
-                _ => Err(Error::invalid_data(
-                    "Tried calling a function with non-constant arg count",
-                )),
-            }?;
-            let args = vm.pop_len(num_args)?;
+            let args = vm.pop_args()?;
             vm.push(Expression::CallMethod { name, object, args })
         }
+        Action::NewObject => {
+            let name = ReferenceExpression::from_expression(vm.pop()?);
+            let args = vm.pop_args()?;
+            vm.push(new_object(Expression::Reference(name), args))
+        }
+        Action::NewMethod => {
+            let name = vm.pop()?;
+            let object = ReferenceExpression::from_expression(vm.pop()?);
+            let args = vm.pop_args()?;
+            // an empty method name constructs the object itself
+            let constructor = match name {
+                Expression::Literal(Variant::String(name)) if name.is_empty() => {
+                    Expression::Reference(object)
+                }
+                Expression::Literal(Variant::Undefined) => Expression::Reference(object),
+                name => Expression::GetMember {
+                    object,
+                    name: ReferenceExpression::from_expression(name),
+                },
+            };
+            vm.push(Expression::New {
+                constructor: Box::new(constructor),
+                args,
+            })
+        }
         Action::Push(push) => {
             for value in push.values.iter() {
                 let expression = match value {
@@ -96,36 +164,46 @@ fn decompile_action(vm: &mut VirtualMachine, action: Action) -> Result<()> {
                     Value::Int(val) => Expression::Literal(Variant::Int(*val)),
                     Value::Float(val) => Expression::Literal(Variant::Float(*val)),
                     Value::Double(val) => Expression::Literal(Variant::Double(*val)),
-                    Value::ConstantPool(id) => {
-                        Expression::Literal(Variant::String(vm.get_constant(*id as usize)))
-                    }
-                    Value::Register(id) => {
-                        Expression::Reference(ReferenceExpression::Register(*id))
-                    }
-                    Value::Str(val) => {
-                        Expression::Literal(Variant::String(val.to_string_lossy(UTF_8)))
-                    }
+                    Value::ConstantPool(id) => Expression::Literal(vm.get_constant(*id as usize)),
+                    Value::Register(id) => decompile_register_read(vm, *id),
+                    Value::Str(val) => Expression::Literal(string_variant(val.as_bytes())),
                 };
                 vm.push(expression)
             }
         }
         Action::PushDuplicate => {
             let value = vm.pop()?;
-            vm.push(value.clone());
-            vm.push(value);
+            if value.is_pure() {
+                vm.push(value.clone());
+                vm.push(value);
+            } else {
+                let name = vm.store_temporary(value);
+                vm.push(Expression::Reference(ReferenceExpression::Identifier(
+                    name.clone(),
+                )));
+                vm.push(Expression::Reference(ReferenceExpression::Identifier(name)));
+            }
         }
         Action::StoreRegister(store) => {
             let value = vm.pop()?;
-            vm.store(store.register, value.clone())?;
-            vm.push(Expression::StoreRegister {
-                id: store.register,
-                value: Box::new(value),
-            })
+            vm.store(store.register)?;
+            match vm.register_name(store.register) {
+                Some(name) => vm.push(Expression::Assign {
+                    target: Box::new(Expression::Reference(ReferenceExpression::Identifier(
+                        name.to_string(),
+                    ))),
+                    operator: None,
+                    value: Box::new(value),
+                }),
+                None => vm.push(Expression::StoreRegister {
+                    id: store.register,
+                    value: Box::new(value),
+                }),
+            }
         }
         Action::If(target) => {
-            // negate the expression
-            decompile_unary_expr(vm, UnaryExpressionType::Not)?;
-            let condition = vm.pop()?;
+            // the block runs when the jump is not taken
+            let condition = negate(vm.pop()?, &vm.data.options);
             vm.jump(target.offset, Some(condition))?;
         }
         Action::Jump(target) => {
@@ -133,7 +211,7 @@ fn decompile_action(vm: &mut VirtualMachine, action: Action) -> Result<()> {
         }
         Action::Pop => {
             let expr = vm.pop()?;
-            vm.append_statement(Statement::ExpressionStatement(expr))
+            append_assignment(vm, Statement::ExpressionStatement(expr))?
         }
         Action::ToInteger => {
             let value = vm.pop()?;
@@ -162,11 +240,18 @@ fn decompile_action(vm: &mut VirtualMachine, action: Action) -> Result<()> {
             vm.push(Expression::GetMember { name, object })
         }
         Action::GetProperty => {
-            let index = vm.pop()?;
+            let property = Property::from_index(vm.pop()?);
             let path = vm.pop()?;
-            vm.push(Expression::GetProperty {
-                index: Box::new(index),
-                path: Box::new(path),
+            let clip = resolve_clip(&path, vm.data.options.keep_slash_syntax);
+            vm.push(match (clip, property) {
+                (Some(clip), Property::Named(name)) => Expression::GetMember {
+                    object: ReferenceExpression::from_expression(clip),
+                    name: ReferenceExpression::Identifier(name.to_string()),
+                },
+                (_, property) => Expression::GetProperty {
+                    path: Box::new(path),
+                    property,
+                },
             })
         }
         Action::InitArray => {
@@ -194,20 +279,15 @@ fn decompile_action(vm: &mut VirtualMachine, action: Action) -> Result<()> {
                 };
             vm.push(Expression::Literal(Variant::Object(props)));
         }
-        Action::GetVariable => {
-            let path = match vm.pop()? {
-                Expression::Literal(Variant::String(str)) => ReferenceExpression::Variable(str),
-                it => ReferenceExpression::from_expression(it),
-            };
-            vm.push(Expression::Reference(path))
+        Action::GetVariable => decompile_get_variable(vm)?,
+        Action::SetVariable => decompile_set_variable(vm)?,
+        Action::SetTarget(target) => {
+            let target = target.target.to_string_lossy(UTF_8);
+            vm.set_target(Expression::Literal(Variant::String(target)))
         }
-        Action::SetVariable => {
-            let value = vm.pop()?;
-            let path = ReferenceExpression::from_expression(vm.pop()?);
-            vm.append_statement(Statement::SetVariable {
-                left: path,
-                right: value.into(),
-            })
+        Action::SetTarget2 => {
+            let target = vm.pop()?;
+            vm.set_target(target)
         }
         Action::Trace => {
             let expr = vm.pop()?;
@@ -223,16 +303,40 @@ fn decompile_action(vm: &mut VirtualMachine, action: Action) -> Result<()> {
             let name = ReferenceExpression::from_expression(vm.pop()?);
             vm.append_statement(Statement::DeclareLocal { name })
         }
-        Action::SetMember | Action::SetProperty => {
+        Action::SetProperty => {
+            let value = vm.pop()?;
+            let property = Property::from_index(vm.pop()?);
+            let path = vm.pop()?;
+            let clip = resolve_clip(&path, vm.data.options.keep_slash_syntax);
+            match (clip, property) {
+                (Some(clip), Property::Named(name)) => append_assignment(
+                    vm,
+                    Statement::SetMember {
+                        object: ReferenceExpression::from_expression(clip),
+                        name: ReferenceExpression::Identifier(name.to_string()),
+                        value,
+                    },
+                )?,
+                (_, property) => vm.append_statement(Statement::SetProperty {
+                    path,
+                    property,
+                    value,
+                }),
+            }
+        }
+        Action::SetMember => {
             let value = vm.pop()?;
             let name = ReferenceExpression::from_expression(vm.pop()?);
             let object = ReferenceExpression::from_expression(vm.pop()?);
 
-            vm.append_statement(Statement::SetMember {
-                object,
-                name,
-                value,
-            })
+            append_assignment(
+                vm,
+                Statement::SetMember {
+                    object,
+                    name,
+                    value,
+                },
+            )?
         }
         Action::Return => {
             let value = vm.pop()?;
@@ -240,9 +344,9 @@ fn decompile_action(vm: &mut VirtualMachine, action: Action) -> Result<()> {
         }
 
         Action::Subtract => decompile_binary_expr(vm, BinaryExpressionType::Subtract)?,
-        Action::Add | Action::Add2 | Action::StringAdd => {
-            decompile_binary_expr(vm, BinaryExpressionType::Add)?
-        }
+        Action::Add => decompile_binary_expr(vm, BinaryExpressionType::NumericAdd)?,
+        Action::Add2 => decompile_binary_expr(vm, BinaryExpressionType::Add)?,
+        Action::StringAdd => decompile_binary_expr(vm, BinaryExpressionType::StringAdd)?,
         Action::Divide => decompile_binary_expr(vm, BinaryExpressionType::Divide)?,
         Action::Multiply => decompile_binary_expr(vm, BinaryExpressionType::Multiply)?,
         Action::Modulo => decompile_binary_expr(vm, BinaryExpressionType::Modulo)?,
@@ -254,29 +358,181 @@ fn decompile_action(vm: &mut VirtualMachine, action: Action) -> Result<()> {
         Action::BitRShift => decompile_binary_expr(vm, BinaryExpressionType::BitRShift)?,
         Action::BitLShift => decompile_binary_expr(vm, BinaryExpressionType::BitLShift)?,
 
-        Action::Greater | Action::StringGreater => {
-            decompile_binary_expr(vm, BinaryExpressionType::Greater)?
+        Action::Greater => decompile_binary_expr(vm, BinaryExpressionType::Greater)?,
+        Action::StringGreater => decompile_binary_expr(vm, BinaryExpressionType::StringGreater)?,
+        Action::Less => decompile_binary_expr(vm, BinaryExpressionType::NumericLess)?,
+        Action::Less2 => decompile_binary_expr(vm, BinaryExpressionType::Less)?,
+        Action::StringLess => decompile_binary_expr(vm, BinaryExpressionType::StringLess)?,
+        Action::Not => decompile_unary_expr(vm, UnaryExpressionType::Not)?,
+        Action::TypeOf => decompile_unary_expr(vm, UnaryExpressionType::TypeOf)?,
+        Action::InstanceOf => decompile_binary_expr(vm, BinaryExpressionType::InstanceOf)?,
+        Action::Delete => {
+            let name = ReferenceExpression::from_expression(vm.pop()?);
+            let object = ReferenceExpression::from_expression(vm.pop()?);
+            vm.push(Expression::Unary {
+                target: Box::new(Expression::GetMember { object, name }),
+                expression_type: UnaryExpressionType::Delete,
+            })
         }
-        Action::Less2 | Action::Less | Action::StringLess => {
-            decompile_binary_expr(vm, BinaryExpressionType::Less)?
+        Action::Delete2 => {
+            let name = ReferenceExpression::from_expression(vm.pop()?);
+            vm.push(Expression::Unary {
+                target: Box::new(Expression::Reference(name)),
+                expression_type: UnaryExpressionType::Delete,
+            })
         }
-        Action::Not => decompile_unary_expr(vm, UnaryExpressionType::Not)?,
         Action::Or => decompile_binary_expr(vm, BinaryExpressionType::LogicalOr)?,
         Action::And => decompile_binary_expr(vm, BinaryExpressionType::LogicalAnd)?,
         Action::StrictEquals => decompile_binary_expr(vm, BinaryExpressionType::StrictEquals)?,
-        Action::Equals | Action::Equals2 | Action::StringEquals => {
-            decompile_binary_expr(vm, BinaryExpressionType::Equals)?
-        }
+        Action::Equals => decompile_binary_expr(vm, BinaryExpressionType::NumericEquals)?,
+        Action::Equals2 => decompile_binary_expr(vm, BinaryExpressionType::Equals)?,
+        Action::StringEquals => decompile_binary_expr(vm, BinaryExpressionType::StringEquals)?,
 
         Action::Increment => decompile_unary_expr(vm, UnaryExpressionType::Increment)?,
         Action::Decrement => decompile_unary_expr(vm, UnaryExpressionType::Decrement)?,
 
-        Action::Stop => vm.append_statement(Statement::Stop),
-        Action::GotoLabel(label) => {
-            vm.append_statement(Statement::GotoLabel(label.label.to_string_lossy(UTF_8)))
+        Action::StackSwap => vm.swap()?,
+        Action::TargetPath => decompile_builtin_call(vm, "targetPath", 1)?,
+        Action::Call => {
+            let frame = vm.pop()?;
+            vm.append_statement(Statement::ExpressionStatement(Expression::CallFunction {
+                name: ReferenceExpression::Identifier("call".to_string()),
+                args: vec![frame],
+            }))
+        }
+        Action::CastOp => {
+            let object = vm.pop()?;
+            let constructor = ReferenceExpression::from_expression(vm.pop()?);
+            vm.push(Expression::CallFunction {
+                name: constructor,
+                args: vec![object],
+            })
         }
-        Action::GotoFrame(frame) => vm.append_statement(Statement::GotoFrame(frame.frame)),
-        Action::Play => vm.append_statement(Statement::Play),
+        Action::ImplementsOp => {
+            let constructor = vm.pop()?;
+            let interfaces = vm.pop_args()?;
+            vm.append_statement(Statement::Implements {
+                constructor,
+                interfaces,
+            })
+        }
+        Action::Extends => {
+            let superclass = vm.pop()?;
+            // the superclass is used twice, so it must only be evaluated once
+            let superclass = if superclass.is_pure() {
+                superclass
+            } else {
+                Expression::Reference(ReferenceExpression::Identifier(
+                    vm.store_temporary(superclass),
+                ))
+            };
+            let subclass = ReferenceExpression::from_expression(vm.pop()?);
+            vm.append_statement(extends(subclass, superclass))
+        }
+        Action::Unknown(unknown) => decompile_unknown(vm, unknown)?,
+        Action::StringLength => decompile_builtin_call(vm, "length", 1)?,
+        Action::StringExtract => decompile_builtin_call(vm, "substring", 3)?,
+        Action::MBStringLength => decompile_builtin_call(vm, "mblength", 1)?,
+        Action::MBStringExtract => decompile_builtin_call(vm, "mbsubstring", 3)?,
+        Action::CharToAscii => decompile_builtin_call(vm, "ord", 1)?,
+        Action::AsciiToChar => decompile_builtin_call(vm, "chr", 1)?,
+        Action::MBCharToAscii => decompile_builtin_call(vm, "mbord", 1)?,
+        Action::MBAsciiToChar => decompile_builtin_call(vm, "mbchr", 1)?,
+        Action::GetTime => decompile_builtin_call(vm, "getTimer", 0)?,
+        Action::RandomNumber => decompile_builtin_call(vm, "random", 1)?,
+
+        Action::Stop => {
+            if !merge_into_goto(vm, false) {
+                vm.append_statement(Statement::Stop)
+            }
+        }
+        Action::GotoLabel(label) => vm.append_statement(Statement::GotoLabel {
+            label: label.label.to_string_lossy(UTF_8),
+            play: false,
+        }),
+        Action::GotoFrame(frame) => vm.append_statement(Statement::GotoFrame {
+            frame: frame.frame,
+            play: false,
+        }),
+        Action::Play => {
+            if !merge_into_goto(vm, true) {
+                vm.append_statement(Statement::Play)
+            }
+        }
+        Action::WaitForFrame(wait) => vm.wait_for_frame(
+            Expression::Literal(Variant::Int(wait.frame as i32 + 1)),
+            wait.num_actions_to_skip,
+        ),
+        Action::WaitForFrame2(wait) => {
+            let frame = vm.pop()?;
+            vm.wait_for_frame(frame, wait.num_actions_to_skip)
+        }
+        Action::GetUrl(get_url) => vm.append_statement(get_url_statement(
+            Expression::Literal(Variant::String(get_url.url.to_string_lossy(UTF_8))),
+            Expression::Literal(Variant::String(get_url.target.to_string_lossy(UTF_8))),
+            false,
+            false,
+            SendVarsMethod::None,
+        )),
+        Action::GetUrl2(flags) => decompile_get_url2(vm, flags)?,
+        Action::NextFrame => vm.append_statement(Statement::NextFrame),
+        Action::PreviousFrame => vm.append_statement(Statement::PrevFrame),
+        Action::GotoFrame2(goto) => {
+            let frame = vm.pop()?;
+            vm.append_statement(Statement::GotoFrame2 {
+                frame,
+                play: goto.set_playing,
+                scene_bias: goto.scene_offset,
+            })
+        }
+        Action::CloneSprite => {
+            let depth = vm.pop()?;
+            let target = vm.pop()?;
+            let source = vm.pop()?;
+            vm.append_statement(Statement::DuplicateMovieClip {
+                source,
+                target,
+                depth: strip_depth_offset(depth),
+            })
+        }
+        Action::RemoveSprite => {
+            let target = vm.pop()?;
+            vm.append_statement(Statement::RemoveMovieClip(target))
+        }
+        Action::StartDrag => {
+            // checked before popping so an error leaves the stack as it was
+            let constrain = match vm.peek_at(2) {
+                Some(Expression::Literal(Variant::Bool(value))) => *value,
+                Some(Expression::Literal(Variant::Int(value))) => *value != 0,
+                Some(Expression::Literal(Variant::Float(value))) => *value != 0.0,
+                Some(Expression::Literal(Variant::Double(value))) => *value != 0.0,
+                _ => {
+                    return Err(Error::invalid_data(
+                        "Tried dragging with non-constant constraint flag",
+                    ))
+                }
+            };
+            let target = vm.pop()?;
+            let lock = vm.pop()?;
+            vm.pop()?;
+            let constraint = if constrain {
+                let bottom = vm.pop()?;
+                let right = vm.pop()?;
+                let top = vm.pop()?;
+                let left = vm.pop()?;
+                Some(Box::new([left, top, right, bottom]))
+            } else {
+                None
+            };
+            vm.append_statement(Statement::StartDrag {
+                target,
+                lock,
+                constraint,
+            })
+        }
+        Action::EndDrag => vm.append_statement(Statement::StopDrag),
+        Action::StopSounds => vm.append_statement(Statement::StopAllSounds),
+        Action::ToggleQuality => vm.append_statement(Statement::ToggleHighQuality),
 
         action => {
             eprintln!("Not implemented: {:?}", action);
@@ -285,3 +541,289 @@ fn decompile_action(vm: &mut VirtualMachine, action: Action) -> Result<()> {
     }
     Ok(())
 }
+
+/// `opcode 0x42 [01 02 03]`, leaving out the brackets for empty payloads
+fn describe_unknown(unknown: &Unknown) -> String {
+    if unknown.data.is_empty() {
+        format!("opcode 0x{:02x}", unknown.opcode)
+    } else {
+        format!(
+            "opcode 0x{:02x} [{}]",
+            unknown.opcode,
+            unknown
+                .data
+                .iter()
+                .map(|byte| format!("{:02x}", byte))
+                .join(" ")
+        )
+    }
+}
+
+/// Turns `new Array(a, b)` and `new Object()` into the equivalent literals,
+/// except `new Array(n)` which creates an array of length `n`
+fn new_object(constructor: Expression, args: Vec<Expression>) -> Expression {
+    let name = match &constructor {
+        Expression::Reference(
+            ReferenceExpression::Identifier(name) | ReferenceExpression::Variable(name),
+        ) => name.as_str(),
+        _ => "",
+    };
+    match name {
+        "Array" if args.len() != 1 => Expression::Literal(Variant::Array(args)),
+        "Object" if args.is_empty() => Expression::Literal(Variant::Object(Vec::new())),
+        _ => Expression::New {
+            constructor: Box::new(constructor),
+            args,
+        },
+    }
+}
+
+/// `duplicateMovieClip` adds 16384 to the depth to keep clones
+/// above the clips placed on the timeline
+fn strip_depth_offset(depth: Expression) -> Expression {
+    match depth {
+        Expression::Binary {
+            left,
+            right,
+            expression_type: BinaryExpressionType::Add | BinaryExpressionType::NumericAdd,
+        } if matches!(*right, Expression::Literal(Variant::Int(16384))) => *left,
+        depth => depth,
+    }
+}
+
+/// SWF4 actions that stand for a global function,
+/// the arguments are pushed in order so the last one is on top
+fn decompile_builtin_call(vm: &mut VirtualMachine, name: &str, num_args: usize) -> Result<()> {
+    let mut args = vm.pop_len(num_args)?;
+    args.reverse();
+    vm.push(Expression::CallFunction {
+        name: ReferenceExpression::Identifier(name.to_string()),
+        args,
+    });
+    Ok(())
+}
+
+/// Opcodes the swf crate doesn't know, either proprietary ones
+/// registered through [OpcodeDecoder] or Flash Lite's `fscommand2`
+fn decompile_unknown(vm: &mut VirtualMachine, unknown: Unknown) -> Result<()> {
+    let effect = vm
+        .data
+        .opcodes
+        .and_then(|decoder| decoder.stack_effect(unknown.opcode, unknown.data));
+    if let Some(effect) = effect {
+        let args = match effect.pops {
+            Pops::Fixed(count) => {
+                let mut args = vm.pop_len(count)?;
+                args.reverse();
+                args
+            }
+            Pops::Counted => vm.pop_args()?,
+        };
+        let call = Expression::CallFunction {
+            name: ReferenceExpression::Identifier(effect.name),
+            args,
+        };
+        if effect.pushes {
+            vm.push(call)
+        } else {
+            vm.append_statement(Statement::ExpressionStatement(call))
+        }
+    } else if unknown.opcode == FS_COMMAND_2 {
+        // the count includes the command, which is on top of the arguments
+        let args = vm.pop_args()?;
+        vm.push(Expression::CallFunction {
+            name: ReferenceExpression::Identifier("fscommand2".to_string()),
+            args,
+        })
+    } else {
+        eprintln!(
+            "Unknown opcode 0x{:02x} at offset {}",
+            unknown.opcode,
+            vm.offset()
+        );
+        vm.append_statement(Statement::UnknownStatement(format!(
+            "unknown {}",
+            describe_unknown(&unknown)
+        )))
+    }
+    Ok(())
+}
+
+/// `ActionExtends` replaces the prototype of the subclass with an object
+/// that inherits from the superclass prototype
+fn extends(subclass: ReferenceExpression, superclass: Expression) -> Statement {
+    let prototype = || ReferenceExpression::Identifier("prototype".to_string());
+    let key = |name: &str| Expression::Literal(Variant::String(name.to_string()));
+    Statement::SetMember {
+        object: subclass,
+        name: prototype(),
+        value: Expression::Literal(Variant::Object(vec![
+            (
+                key("__proto__"),
+                Expression::GetMember {
+                    object: ReferenceExpression::from_expression(superclass.clone()),
+                    name: prototype(),
+                },
+            ),
+            (key("__constructor__"), superclass),
+        ])),
+    }
+}
+
+/// `gotoAndPlay` compiles to a goto followed by `Play` and `gotoAndStop`
+/// to the goto alone, so a `Stop` right after a goto is redundant.
+/// Not if a jump leads to the `Play` or `Stop`, as it also runs without the goto.
+fn merge_into_goto(vm: &mut VirtualMachine, play: bool) -> bool {
+    if vm.is_jump_target() {
+        return false;
+    }
+    match vm.last_statement_mut() {
+        Some(
+            Statement::GotoFrame {
+                play: goto_play @ false,
+                ..
+            }
+            | Statement::GotoLabel {
+                play: goto_play @ false,
+                ..
+            },
+        ) => {
+            *goto_play = play;
+            true
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use swf::avm1::types::{FunctionFlags, GotoFrame, If, Push, StoreRegister};
+    use swf::avm1::write::Writer;
+
+    fn write_actions(actions: &[Action]) -> Vec<u8> {
+        let mut bytecode = vec![];
+        let mut writer = Writer::new(&mut bytecode, 8);
+        for action in actions {
+            writer.write_action(action).unwrap();
+        }
+        bytecode
+    }
+
+    fn decompile_bytecode(mut bytecode: Vec<u8>, constant_pool: &[&str]) -> Vec<String> {
+        bytecode.push(0);
+        let constant_pool: Vec<PoolString> = constant_pool
+            .iter()
+            .map(|it| it.as_bytes().to_vec())
+            .collect();
+        let body = decompile(VmData {
+            bytecode: &bytecode,
+            constant_pool: &constant_pool,
+            ..Default::default()
+        })
+        .unwrap();
+        body.iter().map(|it| it.to_string()).collect()
+    }
+
+    fn decompile_actions(actions: &[Action], constant_pool: &[&str]) -> Vec<String> {
+        decompile_bytecode(write_actions(actions), constant_pool)
+    }
+
+    /// `DefineFunction2` without parameters, with the name at pool index 0
+    /// as the reader expects it
+    fn define_function_2(flags: FunctionFlags, body: &[Action]) -> Vec<u8> {
+        let mut body = write_actions(body);
+        body.push(0);
+        let mut header = vec![0, 0, 0, 0, 4];
+        header.extend(flags.bits().to_le_bytes());
+        header.extend((body.len() as u16).to_le_bytes());
+        let mut bytecode = vec![0x8e];
+        bytecode.extend((header.len() as u16).to_le_bytes());
+        bytecode.extend(header);
+        bytecode.extend(body);
+        bytecode
+    }
+
+    fn push(values: Vec<Value<'static>>) -> Action<'static> {
+        Action::Push(Push { values })
+    }
+
+    #[test]
+    fn merges_play_into_goto() {
+        let actions = [Action::GotoFrame(GotoFrame { frame: 1 }), Action::Play];
+        assert_eq!(decompile_actions(&actions, &[]), ["gotoAndPlay(2)"]);
+    }
+
+    #[test]
+    fn keeps_play_that_a_branch_jumps_to() {
+        // if (a) gotoAndStop(2); play();
+        let actions = [
+            push(vec![Value::ConstantPool(0)]),
+            Action::GetVariable,
+            Action::Not,
+            Action::If(If { offset: 5 }),
+            Action::GotoFrame(GotoFrame { frame: 1 }),
+            Action::Play,
+        ];
+        let body = decompile_actions(&actions, &["a"]);
+        assert_eq!(body[1..], ["gotoAndStop(2)", "play()"]);
+    }
+
+    #[test]
+    fn keeps_stores_that_a_branch_skips() {
+        // if (a) x = $1 = 5; trace($1);
+        let actions = [
+            push(vec![Value::ConstantPool(0)]),
+            Action::GetVariable,
+            Action::Not,
+            Action::If(If { offset: 15 }),
+            push(vec![Value::ConstantPool(1), Value::Int(5)]),
+            Action::StoreRegister(StoreRegister { register: 1 }),
+            Action::SetVariable,
+            push(vec![Value::Register(1)]),
+            Action::Trace,
+        ];
+        let body = decompile_actions(&actions, &["a", "x"]);
+        assert_eq!(body[1..], ["x = $1 = 5", "trace($1)"]);
+    }
+
+    #[test]
+    fn names_preloaded_registers() {
+        let flags = FunctionFlags::PRELOAD_THIS | FunctionFlags::PRELOAD_ROOT;
+        let function = define_function_2(
+            flags,
+            &[
+                push(vec![Value::Register(1), Value::ConstantPool(1)]),
+                Action::GetMember,
+                Action::Trace,
+                push(vec![Value::Register(2)]),
+                Action::Trace,
+            ],
+        );
+        let body = decompile_bytecode(function, &["f", "x"]);
+        assert_eq!(
+            body,
+            ["function f() {\n  trace(this.x)\n  trace(_root)\n}\n"]
+        );
+    }
+
+    #[test]
+    fn keeps_preloaded_registers_that_are_stored_to() {
+        // the store only happens after the read, like at the end of a loop
+        let flags = FunctionFlags::PRELOAD_THIS | FunctionFlags::PRELOAD_ROOT;
+        let function = define_function_2(
+            flags,
+            &[
+                push(vec![Value::Register(1)]),
+                Action::Trace,
+                push(vec![Value::Register(2)]),
+                Action::Trace,
+                push(vec![Value::Int(5)]),
+                Action::StoreRegister(StoreRegister { register: 1 }),
+                Action::Pop,
+            ],
+        );
+        let body = decompile_bytecode(function, &["f"]);
+        assert!(body[0].contains("trace($1)\n  trace(_root)"), "{}", body[0]);
+    }
+}
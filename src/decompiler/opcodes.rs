@@ -0,0 +1,193 @@
+use serde_json::Value;
+use std::collections::HashMap;
+use std::fmt::Debug;
+
+/// Extension point for players that shuffle opcodes or add proprietary ones
+pub trait OpcodeDecoder: Debug {
+    /// The standard opcode that `opcode` stands for in the bytecode
+    fn remap(&self, opcode: u8) -> u8 {
+        opcode
+    }
+
+    /// How a proprietary opcode affects the stack, `None` to decode it as usual.
+    /// `opcode` is already remapped and `data` is the payload after the length field.
+    fn stack_effect(&self, opcode: u8, data: &[u8]) -> Option<StackEffect>;
+}
+
+/// A proprietary opcode, decompiled as a call to `name`
+#[derive(Debug, Clone)]
+pub struct StackEffect {
+    pub name: String,
+    pub pops: Pops,
+    /// Whether the call leaves a result on the stack
+    pub pushes: bool,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum Pops {
+    Fixed(usize),
+    /// The argument count is on top of the arguments, like `ActionCallFunction`
+    Counted,
+}
+
+/// An [OpcodeDecoder] read from a JSON file like
+///
+/// ```json
+/// {
+///   "permutation": { "0x12": "0x96", "0x96": "0x12" },
+///   "opcodes": { "0xc0": { "name": "playSound", "pops": 2, "pushes": false } }
+/// }
+/// ```
+///
+/// `permutation` can also be an array of all 256 opcodes,
+/// and `pops` can be `"count"` for variadic opcodes.
+#[derive(Debug)]
+pub struct OpcodeTable {
+    permutation: [u8; 256],
+    opcodes: HashMap<u8, StackEffect>,
+}
+
+impl Default for OpcodeTable {
+    fn default() -> Self {
+        OpcodeTable {
+            permutation: std::array::from_fn(|it| it as u8),
+            opcodes: HashMap::new(),
+        }
+    }
+}
+
+impl OpcodeTable {
+    pub fn from_json(json: &str) -> Result<OpcodeTable, String> {
+        let config: Value = serde_json::from_str(json).map_err(|it| it.to_string())?;
+        let mut table = OpcodeTable::default();
+
+        match config.get("permutation") {
+            None => {}
+            Some(Value::Array(opcodes)) if opcodes.len() == 256 => {
+                for (from, to) in opcodes.iter().enumerate() {
+                    table.permutation[from] = parse_opcode(to)?;
+                }
+            }
+            Some(Value::Object(opcodes)) => {
+                for (from, to) in opcodes {
+                    table.permutation[parse_opcode_str(from)? as usize] = parse_opcode(to)?;
+                }
+            }
+            Some(_) => return Err("permutation must be an object or 256 opcodes".to_string()),
+        }
+
+        if let Some(opcodes) = config.get("opcodes") {
+            let opcodes = opcodes.as_object().ok_or("opcodes must be an object")?;
+            for (opcode, effect) in opcodes {
+                let name = effect
+                    .get("name")
+                    .and_then(Value::as_str)
+                    .ok_or_else(|| format!("opcode {} is missing a name", opcode))?;
+                let pops = match effect.get("pops") {
+                    None => Pops::Fixed(0),
+                    Some(Value::String(count)) if count == "count" => Pops::Counted,
+                    Some(pops) => Pops::Fixed(
+                        pops.as_u64()
+                            .ok_or_else(|| format!("invalid pops for opcode {}", opcode))?
+                            as usize,
+                    ),
+                };
+                let pushes = effect
+                    .get("pushes")
+                    .and_then(Value::as_bool)
+                    .unwrap_or(false);
+                table.opcodes.insert(
+                    parse_opcode_str(opcode)?,
+                    StackEffect {
+                        name: name.to_string(),
+                        pops,
+                        pushes,
+                    },
+                );
+            }
+        }
+
+        Ok(table)
+    }
+}
+
+impl OpcodeDecoder for OpcodeTable {
+    fn remap(&self, opcode: u8) -> u8 {
+        self.permutation[opcode as usize]
+    }
+
+    fn stack_effect(&self, opcode: u8, _data: &[u8]) -> Option<StackEffect> {
+        self.opcodes.get(&opcode).cloned()
+    }
+}
+
+fn parse_opcode(value: &Value) -> Result<u8, String> {
+    match value {
+        Value::String(opcode) => parse_opcode_str(opcode),
+        Value::Number(opcode) => opcode
+            .as_u64()
+            .and_then(|it| u8::try_from(it).ok())
+            .ok_or_else(|| format!("invalid opcode {}", opcode)),
+        _ => Err(format!("invalid opcode {}", value)),
+    }
+}
+
+/// `"0x96"` or `"150"`
+fn parse_opcode_str(opcode: &str) -> Result<u8, String> {
+    match opcode.strip_prefix("0x") {
+        Some(hex) => u8::from_str_radix(hex, 16),
+        None => opcode.parse(),
+    }
+    .map_err(|_| format!("invalid opcode {}", opcode))
+}
+
+/// Replaces every opcode with the one it stands for, walking the action
+/// headers. Like in `read`, whether an action has a length field depends
+/// on the remapped opcode, payloads and jump offsets stay as they are.
+pub fn remap_opcodes(bytecode: &[u8], decoder: &dyn OpcodeDecoder) -> Vec<u8> {
+    let mut remapped = bytecode.to_vec();
+    let mut pos = 0;
+    while pos < remapped.len() {
+        let opcode = decoder.remap(bytecode[pos]);
+        remapped[pos] = opcode;
+        pos += 1;
+        if opcode >= 0x80 {
+            let length = match bytecode.get(pos..pos + 2) {
+                Some(length) => u16::from_le_bytes([length[0], length[1]]) as usize,
+                None => break,
+            };
+            pos += 2 + length;
+        }
+    }
+    remapped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn remaps_push_across_the_length_boundary() {
+        let table =
+            OpcodeTable::from_json(r#"{ "permutation": { "0x12": "0x96", "0x96": "0x12" } }"#)
+                .unwrap();
+        // push 0x96 (as an int), not, trace, end
+        let bytecode = [0x12, 5, 0, 7, 0x96, 0, 0, 0, 0x96, 0x26, 0];
+        assert_eq!(
+            remap_opcodes(&bytecode, &table),
+            [0x96, 5, 0, 7, 0x96, 0, 0, 0, 0x12, 0x26, 0]
+        );
+    }
+
+    #[test]
+    fn reads_permutation_arrays() {
+        let mut opcodes: Vec<u8> = (0..=255).collect();
+        opcodes.swap(0x07, 0x26);
+        let json = format!("{{ \"permutation\": {:?} }}", opcodes);
+        let table = OpcodeTable::from_json(&json).unwrap();
+        assert_eq!(table.remap(0x07), 0x26);
+        assert_eq!(table.remap(0x26), 0x07);
+        assert_eq!(table.remap(0x96), 0x96);
+        assert!(OpcodeTable::from_json(r#"{ "permutation": [1, 2] }"#).is_err());
+    }
+}
@@ -0,0 +1,161 @@
+use crate::ast::binary_expr::BinaryExpressionType;
+use crate::ast::expr::{Expression, ReferenceExpression, UnaryExpressionType};
+use crate::ast::statement::Statement;
+use crate::ast::variant::Variant;
+use crate::ast::visit::{walk_expression, walk_statement, VisitMut};
+
+/// Turns `x = x + e` into `x += e`, `x = x - 1` into `x--` and so on.
+///
+/// `x = x + 1` deliberately becomes `x += 1` rather than `x++`,
+/// because `+` concatenates strings while `++` always increments.
+pub fn recover_compound_assignments(body: &mut Vec<Statement>) {
+    CompoundAssignments.visit_statements(body)
+}
+
+/// The update operator (`++x`, `x--`...) that assigning `value` to `target` amounts to
+pub fn as_update(
+    target: &Expression,
+    value: &Expression,
+    prefix: bool,
+) -> Option<UnaryExpressionType> {
+    let increment = match value {
+        Expression::Unary {
+            target: operand,
+            expression_type: UnaryExpressionType::Increment,
+        } if target.is_same_target(operand) => true,
+        Expression::Unary {
+            target: operand,
+            expression_type: UnaryExpressionType::Decrement,
+        } if target.is_same_target(operand) => false,
+        Expression::Binary {
+            left,
+            right,
+            expression_type: BinaryExpressionType::Subtract,
+        } if target.is_same_target(left) && is_one(right) => false,
+        _ => return None,
+    };
+    Some(match (increment, prefix) {
+        (true, true) => UnaryExpressionType::PreIncrement,
+        (true, false) => UnaryExpressionType::PostIncrement,
+        (false, true) => UnaryExpressionType::PreDecrement,
+        (false, false) => UnaryExpressionType::PostDecrement,
+    })
+}
+
+fn is_one(expression: &Expression) -> bool {
+    match expression {
+        Expression::Literal(Variant::Int(value)) => *value == 1,
+        Expression::Literal(Variant::Float(value)) => *value == 1.0,
+        Expression::Literal(Variant::Double(value)) => *value == 1.0,
+        _ => false,
+    }
+}
+
+fn is_compound_operator(operator: &BinaryExpressionType) -> bool {
+    matches!(
+        operator,
+        BinaryExpressionType::Add
+            | BinaryExpressionType::Subtract
+            | BinaryExpressionType::Multiply
+            | BinaryExpressionType::Divide
+            | BinaryExpressionType::Modulo
+            | BinaryExpressionType::BitAnd
+            | BinaryExpressionType::BitOr
+            | BinaryExpressionType::BitXor
+            | BinaryExpressionType::BitLShift
+            | BinaryExpressionType::BitRShift
+            | BinaryExpressionType::BitURShift
+    )
+}
+
+fn is_compound(target: &Expression, value: &Expression) -> bool {
+    as_update(target, value, false).is_some()
+        || matches!(value, Expression::Binary { left, expression_type, .. }
+            if is_compound_operator(expression_type) && target.is_same_target(left))
+}
+
+fn take(expression: &mut Expression) -> Expression {
+    std::mem::replace(expression, Expression::Literal(Variant::Undefined))
+}
+
+/// Rewrites the assignment of `value` to `target`, returns
+/// the plain assignment if no shorter form applies
+fn compound(target: Expression, value: Expression, prefix: bool) -> Expression {
+    if let Some(expression_type) = as_update(&target, &value, prefix) {
+        return Expression::Unary {
+            target: Box::new(target),
+            expression_type,
+        };
+    }
+    match value {
+        Expression::Binary {
+            left,
+            right,
+            expression_type,
+        } if is_compound_operator(&expression_type) && target.is_same_target(&left) => {
+            Expression::Assign {
+                target: Box::new(target),
+                operator: Some(expression_type),
+                value: right,
+            }
+        }
+        value => Expression::Assign {
+            target: Box::new(target),
+            operator: None,
+            value: Box::new(value),
+        },
+    }
+}
+
+/// Rewrites plain assignments and register stores in place,
+/// `prefix` decides which form an increment takes
+fn compound_in_place(expression: &mut Expression, prefix: bool) {
+    let (target, value) = match expression {
+        Expression::Assign {
+            target,
+            operator: None,
+            value,
+        } => (target.as_ref().clone(), value),
+        Expression::StoreRegister { id, value } => (
+            Expression::Reference(ReferenceExpression::Register(*id)),
+            value,
+        ),
+        _ => return,
+    };
+    if is_compound(&target, value) {
+        let value = take(value);
+        *expression = compound(target, value, prefix);
+    }
+}
+
+struct CompoundAssignments;
+
+impl VisitMut for CompoundAssignments {
+    fn visit_statement(&mut self, statement: &mut Statement) {
+        if let Statement::ExpressionStatement(expression) = statement {
+            // the value is unused, so `x++` reads better than `++x`
+            walk_expression(self, expression);
+            compound_in_place(expression, false);
+            return;
+        }
+
+        walk_statement(self, statement);
+        if let (Some(target), Some(value)) =
+            (statement.assignment_target(), statement.assigned_value())
+        {
+            if is_compound(&target, value) {
+                let value = match statement {
+                    Statement::SetVariable { right, .. } => take(right),
+                    Statement::SetMember { value, .. } => take(value),
+                    _ => unreachable!("Only assignments have a target"),
+                };
+                *statement = Statement::ExpressionStatement(compound(target, value, false));
+            }
+        }
+    }
+
+    fn visit_expression(&mut self, expression: &mut Expression) {
+        walk_expression(self, expression);
+        compound_in_place(expression, true);
+    }
+}
@@ -0,0 +1,4 @@
+pub mod compound_assignment;
+pub mod registers;
+pub mod simplify;
+pub mod temporaries;
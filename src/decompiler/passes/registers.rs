@@ -0,0 +1,55 @@
+use crate::ast::expr::{Expression, ReferenceExpression};
+use crate::ast::statement::Statement;
+use crate::ast::variant::Variant;
+use crate::ast::visit::{walk_expression, walk_reference, walk_statement, VisitMut};
+use std::collections::HashSet;
+
+/// Removes register stores that are never read again,
+/// usually left behind by recovered assignment expressions.
+pub fn strip_unread_registers(body: &mut Vec<Statement>) {
+    let mut reads = RegisterReads::default();
+    reads.visit_statements(body);
+    StripStores { read: reads.read }.visit_statements(body);
+}
+
+#[derive(Default)]
+struct RegisterReads {
+    read: HashSet<u8>,
+}
+
+impl VisitMut for RegisterReads {
+    fn visit_reference(&mut self, reference: &mut ReferenceExpression) {
+        if let ReferenceExpression::Register(id) = reference {
+            self.read.insert(*id);
+        }
+        walk_reference(self, reference)
+    }
+}
+
+struct StripStores {
+    read: HashSet<u8>,
+}
+
+impl VisitMut for StripStores {
+    fn visit_statement(&mut self, statement: &mut Statement) {
+        match statement {
+            // `$1 = value` on its own is still meaningful to the reader
+            Statement::ExpressionStatement(Expression::StoreRegister { value, .. }) => {
+                self.visit_expression(value)
+            }
+            _ => walk_statement(self, statement),
+        }
+    }
+
+    fn visit_expression(&mut self, expression: &mut Expression) {
+        if let Expression::StoreRegister { id, value } = expression {
+            if !self.read.contains(id) {
+                let value =
+                    std::mem::replace(value.as_mut(), Expression::Literal(Variant::Undefined));
+                *expression = value;
+                return self.visit_expression(expression);
+            }
+        }
+        walk_expression(self, expression)
+    }
+}
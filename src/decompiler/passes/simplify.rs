@@ -0,0 +1,646 @@
+use crate::ast::binary_expr::BinaryExpressionType;
+use crate::ast::expr::{Expression, UnaryExpressionType};
+use crate::ast::statement::Statement;
+use crate::ast::variant::Variant;
+use crate::ast::visit::{walk_expression, walk_statement, VisitMut};
+use crate::decompiler::DecompileOptions;
+
+/// Folds constant expressions like `"a" + "b" + 1` or `60 * 60` and removes
+/// identities like `x * 1`.
+///
+/// Only coercions that behave the same in every player version are folded,
+/// so anything involving `undefined`, `null`, string to number or boolean to
+/// string conversion stays. `!!x` is only dropped where just its truthiness matters.
+pub fn simplify_expressions(body: &mut Vec<Statement>, options: &DecompileOptions) {
+    Simplifier { options }.visit_statements(body)
+}
+
+enum Constant {
+    Number(f64),
+    String(String),
+    Bool(bool),
+}
+
+impl Constant {
+    fn from_expression(expression: &Expression) -> Option<Constant> {
+        match expression {
+            Expression::Literal(Variant::Int(value)) => Some(Constant::Number(*value as f64)),
+            Expression::Literal(Variant::Float(value)) => Some(Constant::Number(*value as f64)),
+            Expression::Literal(Variant::Double(value)) => Some(Constant::Number(*value)),
+            Expression::Literal(Variant::String(value)) => Some(Constant::String(value.clone())),
+            Expression::Literal(Variant::Bool(value)) => Some(Constant::Bool(*value)),
+            _ => None,
+        }
+    }
+
+    fn to_number(&self) -> Option<f64> {
+        match self {
+            Constant::Number(value) => Some(*value),
+            Constant::Bool(value) => Some(if *value { 1.0 } else { 0.0 }),
+            Constant::String(_) => None,
+        }
+    }
+
+    /// Booleans are `1` and `0` in SWF4 but `true` and `false` later on
+    fn to_avm1_string(&self) -> Option<String> {
+        match self {
+            Constant::Number(value) => Some(number_to_string(*value)),
+            Constant::String(value) => Some(value.clone()),
+            Constant::Bool(_) => None,
+        }
+    }
+}
+
+fn number(value: f64) -> Expression {
+    let is_int = value.fract() == 0.0
+        && value >= i32::MIN as f64
+        && value <= i32::MAX as f64
+        && !(value == 0.0 && value.is_sign_negative());
+    Expression::Literal(if is_int {
+        Variant::Int(value as i32)
+    } else {
+        Variant::Double(value)
+    })
+}
+
+fn string(value: String) -> Expression {
+    Expression::Literal(Variant::String(value))
+}
+
+/// `ToInt32`, used by all bit operations
+fn to_int32(value: f64) -> i32 {
+    if !value.is_finite() {
+        return 0;
+    }
+    value.trunc().rem_euclid(4294967296.0) as u32 as i32
+}
+
+/// Numbers are converted to strings with 15 significant digits,
+/// switching to exponential notation outside of `1e-5..1e15`
+fn number_to_string(value: f64) -> String {
+    if value.is_nan() {
+        return "NaN".to_string();
+    }
+    if value.is_infinite() {
+        return if value < 0.0 { "-Infinity" } else { "Infinity" }.to_string();
+    }
+    if value == 0.0 {
+        return "0".to_string();
+    }
+
+    let exponential = format!("{:.14e}", value.abs());
+    let (mantissa, exponent) = exponential
+        .split_once('e')
+        .expect("LowerExp always has an exponent");
+    let exponent: i32 = exponent.parse().expect("LowerExp exponent is an integer");
+    let digits = mantissa.replace('.', "");
+    let digits = digits.trim_end_matches('0');
+    let sign = if value < 0.0 { "-" } else { "" };
+
+    if !(-5..15).contains(&exponent) {
+        let (first, rest) = digits.split_at(1);
+        let exponent_sign = if exponent < 0 { "-" } else { "+" };
+        let fraction = if rest.is_empty() {
+            String::new()
+        } else {
+            format!(".{}", rest)
+        };
+        format!(
+            "{}{}{}e{}{}",
+            sign,
+            first,
+            fraction,
+            exponent_sign,
+            exponent.abs()
+        )
+    } else if exponent < 0 {
+        format!(
+            "{}0.{}{}",
+            sign,
+            "0".repeat((-exponent - 1) as usize),
+            digits
+        )
+    } else {
+        let point = exponent as usize + 1;
+        if digits.len() <= point {
+            format!("{}{}{}", sign, digits, "0".repeat(point - digits.len()))
+        } else {
+            format!("{}{}.{}", sign, &digits[..point], &digits[point..])
+        }
+    }
+}
+
+fn fold_binary(
+    expression_type: &BinaryExpressionType,
+    left: &Constant,
+    right: &Constant,
+) -> Option<Expression> {
+    let is_string = |it: &Constant| matches!(it, Constant::String(_));
+    let concatenates = match expression_type {
+        BinaryExpressionType::StringAdd => true,
+        BinaryExpressionType::Add => is_string(left) || is_string(right),
+        _ => false,
+    };
+    if concatenates {
+        return Some(string(format!(
+            "{}{}",
+            left.to_avm1_string()?,
+            right.to_avm1_string()?
+        )));
+    }
+
+    let (a, b) = (left.to_number()?, right.to_number()?);
+    Some(match expression_type {
+        BinaryExpressionType::Add | BinaryExpressionType::NumericAdd => number(a + b),
+        BinaryExpressionType::Subtract => number(a - b),
+        BinaryExpressionType::Multiply => number(a * b),
+        // SWF4 players print `#ERROR#` instead of Infinity
+        BinaryExpressionType::Divide if b != 0.0 => number(a / b),
+        BinaryExpressionType::Modulo if b != 0.0 => number(a % b),
+        BinaryExpressionType::BitAnd => number((to_int32(a) & to_int32(b)) as f64),
+        BinaryExpressionType::BitOr => number((to_int32(a) | to_int32(b)) as f64),
+        BinaryExpressionType::BitXor => number((to_int32(a) ^ to_int32(b)) as f64),
+        BinaryExpressionType::BitLShift => {
+            number(to_int32(a).wrapping_shl(to_int32(b) as u32 & 31) as f64)
+        }
+        BinaryExpressionType::BitRShift => {
+            number(to_int32(a).wrapping_shr(to_int32(b) as u32 & 31) as f64)
+        }
+        BinaryExpressionType::BitURShift => {
+            number(((to_int32(a) as u32) >> (to_int32(b) as u32 & 31)) as f64)
+        }
+        _ => return None,
+    })
+}
+
+fn is_literal(expression: &Expression, expected: f64) -> bool {
+    matches!(Constant::from_expression(expression), Some(Constant::Number(value)) if value == expected)
+}
+
+/// Whether the expression is a number that can't be `-0`
+fn is_int32(expression: &Expression) -> bool {
+    matches!(
+        expression,
+        Expression::Binary {
+            expression_type: BinaryExpressionType::BitAnd
+                | BinaryExpressionType::BitOr
+                | BinaryExpressionType::BitXor
+                | BinaryExpressionType::BitLShift
+                | BinaryExpressionType::BitRShift
+                | BinaryExpressionType::BitURShift,
+            ..
+        } | Expression::Unary {
+            expression_type: UnaryExpressionType::ToInteger,
+            ..
+        }
+    )
+}
+
+/// `x - 0`, `x * 1` and `x / 1` don't change numbers. `x + 0` turns `-0`
+/// into `0`, so it's only dropped for values that are never `-0`.
+fn remove_identity(
+    expression_type: &BinaryExpressionType,
+    left: &Expression,
+    right: &Expression,
+) -> Option<Expression> {
+    match expression_type {
+        BinaryExpressionType::Subtract if left.is_number() && is_literal(right, 0.0) => {
+            Some(left.clone())
+        }
+        BinaryExpressionType::Multiply | BinaryExpressionType::Divide
+            if left.is_number() && is_literal(right, 1.0) =>
+        {
+            Some(left.clone())
+        }
+        BinaryExpressionType::Multiply if right.is_number() && is_literal(left, 1.0) => {
+            Some(right.clone())
+        }
+        BinaryExpressionType::Add | BinaryExpressionType::NumericAdd
+            if is_int32(left) && is_literal(right, 0.0) =>
+        {
+            Some(left.clone())
+        }
+        BinaryExpressionType::Add | BinaryExpressionType::NumericAdd
+            if is_int32(right) && is_literal(left, 0.0) =>
+        {
+            Some(right.clone())
+        }
+        _ => None,
+    }
+}
+
+fn simplify_unary(
+    expression_type: &UnaryExpressionType,
+    target: &Expression,
+) -> Option<Expression> {
+    let constant = Constant::from_expression(target);
+    match (expression_type, constant) {
+        (UnaryExpressionType::Not, Some(Constant::Bool(value))) => {
+            Some(Expression::Literal(Variant::Bool(!value)))
+        }
+        (UnaryExpressionType::Negate, Some(Constant::Number(value))) => Some(number(-value)),
+        (UnaryExpressionType::Increment, Some(Constant::Number(value))) => {
+            Some(number(value + 1.0))
+        }
+        (UnaryExpressionType::Decrement, Some(Constant::Number(value))) => {
+            Some(number(value - 1.0))
+        }
+        (UnaryExpressionType::ToString, Some(constant)) => constant.to_avm1_string().map(string),
+        (
+            UnaryExpressionType::ToNumber,
+            Some(constant @ (Constant::Number(_) | Constant::Bool(_))),
+        ) => constant.to_number().map(number),
+        (UnaryExpressionType::ToNumber, None) if target.is_number() => Some(target.clone()),
+        (UnaryExpressionType::ToString, None) if is_string(target) => Some(target.clone()),
+        (UnaryExpressionType::Negate, None) => match target {
+            Expression::Unary {
+                target,
+                expression_type: UnaryExpressionType::Negate,
+            } if target.is_number() => Some(target.as_ref().clone()),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+fn is_string(expression: &Expression) -> bool {
+    matches!(
+        expression,
+        Expression::Literal(Variant::String(_))
+            | Expression::Binary {
+                expression_type: BinaryExpressionType::StringAdd,
+                ..
+            }
+            | Expression::Unary {
+                expression_type: UnaryExpressionType::ToString | UnaryExpressionType::TypeOf,
+                ..
+            }
+    )
+}
+
+/// Negates a condition, removing double negations, flipping comparisons
+/// and applying De Morgan's laws if that leaves fewer negations behind.
+///
+/// Only valid where the value is used for its truthiness alone,
+/// `y = !!x` would otherwise turn into `y = x`.
+pub fn negate(expression: Expression, options: &DecompileOptions) -> Expression {
+    match expression {
+        Expression::Unary {
+            target,
+            expression_type: UnaryExpressionType::Not,
+        } => {
+            let mut condition = *target;
+            simplify_condition(&mut condition, options);
+            condition
+        }
+        Expression::Binary {
+            left,
+            right,
+            expression_type,
+        } => {
+            let negated = expression_type.negated().or_else(|| {
+                expression_type.negated_comparison().filter(|_| {
+                    options.assume_no_nan || (is_never_nan(&left) && is_never_nan(&right))
+                })
+            });
+            if let Some(negated) = negated {
+                return Expression::Binary {
+                    left,
+                    right,
+                    expression_type: negated,
+                };
+            }
+
+            let de_morgan = match expression_type {
+                BinaryExpressionType::LogicalAnd => Some(BinaryExpressionType::LogicalOr),
+                BinaryExpressionType::LogicalOr => Some(BinaryExpressionType::LogicalAnd),
+                _ => None,
+            };
+            if let Some(de_morgan) = de_morgan {
+                let negated_left = negate(left.as_ref().clone(), options);
+                let negated_right = negate(right.as_ref().clone(), options);
+                if count_negations(&negated_left) + count_negations(&negated_right)
+                    < 1 + count_negations(&left) + count_negations(&right)
+                {
+                    return Expression::Binary {
+                        left: Box::new(negated_left),
+                        right: Box::new(negated_right),
+                        expression_type: de_morgan,
+                    };
+                }
+            }
+
+            let mut condition = Expression::Binary {
+                left,
+                right,
+                expression_type,
+            };
+            simplify_condition(&mut condition, options);
+            not(condition)
+        }
+        expression => not(expression),
+    }
+}
+
+/// Simplifies an expression that is only used for its truthiness,
+/// which also holds for the operands of `&&` and `||` in it
+fn simplify_condition(condition: &mut Expression, options: &DecompileOptions) {
+    match condition {
+        Expression::Unary {
+            target,
+            expression_type: UnaryExpressionType::Not,
+        } => {
+            let target =
+                std::mem::replace(target.as_mut(), Expression::Literal(Variant::Undefined));
+            *condition = negate(target, options);
+        }
+        Expression::Binary {
+            left,
+            right,
+            expression_type: BinaryExpressionType::LogicalAnd | BinaryExpressionType::LogicalOr,
+        } => {
+            simplify_condition(left, options);
+            simplify_condition(right, options);
+        }
+        _ => {}
+    }
+}
+
+fn not(expression: Expression) -> Expression {
+    Expression::Unary {
+        target: Box::new(expression),
+        expression_type: UnaryExpressionType::Not,
+    }
+}
+
+fn count_negations(expression: &Expression) -> usize {
+    match expression {
+        Expression::Unary {
+            target,
+            expression_type: UnaryExpressionType::Not,
+        } => 1 + count_negations(target),
+        Expression::Binary {
+            left,
+            right,
+            expression_type: BinaryExpressionType::LogicalAnd | BinaryExpressionType::LogicalOr,
+        } => count_negations(left) + count_negations(right),
+        _ => 0,
+    }
+}
+
+fn is_never_nan(expression: &Expression) -> bool {
+    match expression {
+        Expression::Literal(Variant::Int(_) | Variant::Bool(_)) => true,
+        Expression::Literal(Variant::Float(value)) => !value.is_nan(),
+        Expression::Literal(Variant::Double(value)) => !value.is_nan(),
+        Expression::Binary {
+            expression_type, ..
+        } => matches!(
+            expression_type,
+            BinaryExpressionType::BitAnd
+                | BinaryExpressionType::BitOr
+                | BinaryExpressionType::BitXor
+                | BinaryExpressionType::BitLShift
+                | BinaryExpressionType::BitRShift
+                | BinaryExpressionType::BitURShift
+        ),
+        _ => false,
+    }
+}
+
+struct Simplifier<'a> {
+    options: &'a DecompileOptions,
+}
+
+impl VisitMut for Simplifier<'_> {
+    fn visit_statement(&mut self, statement: &mut Statement) {
+        walk_statement(self, statement);
+        match statement {
+            Statement::If { condition, .. }
+            | Statement::While { condition, .. }
+            | Statement::For { condition, .. } => simplify_condition(condition, self.options),
+            _ => {}
+        }
+    }
+
+    fn visit_expression(&mut self, expression: &mut Expression) {
+        // nested functions are simplified when they are decompiled
+        if matches!(expression, Expression::Function { .. }) {
+            return;
+        }
+        walk_expression(self, expression);
+        match expression {
+            Expression::Ternary { condition, .. } => simplify_condition(condition, self.options),
+            Expression::Unary {
+                target,
+                expression_type: UnaryExpressionType::Not,
+            } => simplify_condition(target, self.options),
+            _ => {}
+        }
+
+        let simplified = match expression {
+            Expression::Binary {
+                left,
+                right,
+                expression_type,
+            } => match (
+                Constant::from_expression(left),
+                Constant::from_expression(right),
+            ) {
+                (Some(left), Some(right)) => fold_binary(expression_type, &left, &right),
+                _ => remove_identity(expression_type, left, right),
+            },
+            Expression::Unary {
+                target,
+                expression_type,
+            } => simplify_unary(expression_type, target),
+            _ => None,
+        };
+        if let Some(simplified) = simplified {
+            *expression = simplified
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ast::expr::ReferenceExpression;
+
+    fn fold(expression_type: BinaryExpressionType, left: Constant, right: Constant) -> String {
+        fold_binary(&expression_type, &left, &right)
+            .map(|it| it.to_string())
+            .unwrap_or_default()
+    }
+
+    fn not(target: Expression) -> Expression {
+        Expression::Unary {
+            target: Box::new(target),
+            expression_type: UnaryExpressionType::Not,
+        }
+    }
+
+    fn variable(name: &str) -> Expression {
+        Expression::Reference(ReferenceExpression::Variable(name.to_string()))
+    }
+
+    #[test]
+    fn folds_numbers() {
+        use Constant::Number;
+        assert_eq!(
+            fold(BinaryExpressionType::Add, Number(60.0), Number(0.5)),
+            "60.5"
+        );
+        assert_eq!(
+            fold(BinaryExpressionType::Multiply, Number(60.0), Number(60.0)),
+            "3600"
+        );
+        assert_eq!(
+            fold(BinaryExpressionType::Subtract, Number(1.0), Number(3.0)),
+            "-2"
+        );
+        assert_eq!(
+            fold(BinaryExpressionType::Modulo, Number(-7.0), Number(3.0)),
+            "-1"
+        );
+        assert_eq!(
+            fold(BinaryExpressionType::Divide, Number(1.0), Number(0.0)),
+            ""
+        );
+        assert_eq!(
+            fold(BinaryExpressionType::Modulo, Number(1.0), Number(0.0)),
+            ""
+        );
+        assert_eq!(
+            fold(
+                BinaryExpressionType::NumericAdd,
+                Constant::Bool(true),
+                Number(1.0)
+            ),
+            "2"
+        );
+    }
+
+    #[test]
+    fn folds_strings_but_not_booleans() {
+        let a = || Constant::String("a".to_string());
+        assert_eq!(
+            fold(BinaryExpressionType::Add, a(), Constant::Number(0.1)),
+            "\"a0.1\""
+        );
+        assert_eq!(
+            fold(BinaryExpressionType::StringAdd, Constant::Number(1e21), a()),
+            "\"1e+21a\""
+        );
+        assert_eq!(
+            fold(BinaryExpressionType::Add, Constant::Bool(true), a()),
+            ""
+        );
+        assert_eq!(
+            fold(BinaryExpressionType::StringAdd, a(), Constant::Bool(false)),
+            ""
+        );
+    }
+
+    #[test]
+    fn converts_numbers_to_strings() {
+        assert_eq!(number_to_string(0.1 + 0.2), "0.3");
+        assert_eq!(number_to_string(1.0 / 3.0), "0.333333333333333");
+        assert_eq!(number_to_string(-0.0), "0");
+        assert_eq!(number_to_string(f64::NAN), "NaN");
+        assert_eq!(number_to_string(f64::NEG_INFINITY), "-Infinity");
+        assert_eq!(number_to_string(123456789012345.0), "123456789012345");
+        assert_eq!(number_to_string(1e15), "1e+15");
+        assert_eq!(number_to_string(1.5e15), "1.5e+15");
+        assert_eq!(number_to_string(0.00001), "0.00001");
+        assert_eq!(number_to_string(0.000001), "1e-6");
+        assert_eq!(number_to_string(-2.5e-7), "-2.5e-7");
+    }
+
+    #[test]
+    fn does_bit_operations_on_int32() {
+        use Constant::Number;
+        assert_eq!(to_int32(4294967297.0), 1);
+        assert_eq!(to_int32(2147483648.0), i32::MIN);
+        assert_eq!(to_int32(-1.5), -1);
+        assert_eq!(to_int32(f64::INFINITY), 0);
+        assert_eq!(
+            fold(BinaryExpressionType::BitURShift, Number(-1.0), Number(0.0)),
+            "4294967295"
+        );
+        assert_eq!(
+            fold(BinaryExpressionType::BitLShift, Number(1.0), Number(33.0)),
+            "2"
+        );
+        assert_eq!(
+            fold(BinaryExpressionType::BitLShift, Number(1.0), Number(31.0)),
+            "-2147483648"
+        );
+        assert_eq!(
+            fold(BinaryExpressionType::BitRShift, Number(-8.0), Number(1.0)),
+            "-4"
+        );
+        assert_eq!(
+            fold(
+                BinaryExpressionType::BitAnd,
+                Number(4294967295.0),
+                Number(255.0)
+            ),
+            "255"
+        );
+        assert_eq!(
+            fold(BinaryExpressionType::BitXor, Number(5.0), Number(3.0)),
+            "6"
+        );
+    }
+
+    #[test]
+    fn removes_double_negations_only_in_conditions() {
+        let mut body = vec![
+            Statement::If {
+                condition: not(not(variable("x"))),
+                true_branch: None,
+                false_branch: None,
+            },
+            Statement::SetVariable {
+                left: ReferenceExpression::Variable("y".to_string()),
+                right: Box::new(not(not(variable("x")))),
+            },
+            Statement::Trace(not(not(not(variable("x"))))),
+        ];
+        simplify_expressions(&mut body, &DecompileOptions::default());
+        let printed: Vec<String> = body.iter().map(|it| it.to_string()).collect();
+        assert_eq!(
+            printed,
+            ["if (x) { /* unresolved */ }", "y = !!x", "trace(!x)"]
+        );
+    }
+
+    #[test]
+    fn negates_conditions_like_the_decompiler() {
+        let less = || Expression::Binary {
+            left: Box::new(variable("a")),
+            right: Box::new(variable("b")),
+            expression_type: BinaryExpressionType::Less,
+        };
+        let simplified = |options: DecompileOptions| {
+            let mut body = vec![Statement::If {
+                condition: not(less()),
+                true_branch: None,
+                false_branch: None,
+            }];
+            simplify_expressions(&mut body, &options);
+            let Statement::If { condition, .. } = &body[0] else {
+                unreachable!()
+            };
+            assert_eq!(condition.to_string(), negate(less(), &options).to_string());
+            condition.to_string()
+        };
+        assert_eq!(simplified(DecompileOptions::default()), "!(a < b)");
+        let assume_no_nan = DecompileOptions {
+            assume_no_nan: true,
+            ..Default::default()
+        };
+        assert_eq!(simplified(assume_no_nan), "a >= b");
+    }
+}
@@ -0,0 +1,216 @@
+use crate::ast::expr::{Expression, ReferenceExpression};
+use crate::ast::statement::Statement;
+use crate::ast::visit::{
+    walk_expression, walk_reference, walk_statement, walk_statements, VisitMut,
+};
+
+/// Temporaries are introduced for every impure [swf::avm1::types::Action::PushDuplicate].
+/// Quite often only one of the two copies is actually used and the other one
+/// is simply popped, in which case the value is moved back to where it is used,
+/// as long as that is the very next statement.
+pub fn inline_temporaries(body: &mut Vec<Statement>, temporaries: &[String]) {
+    if temporaries.is_empty() {
+        return;
+    }
+
+    DropPoppedCopies { temporaries }.visit_statements(body);
+    let mut counter = UseCounter {
+        temporaries,
+        uses: vec![0; temporaries.len()],
+    };
+    counter.visit_statements(body);
+    Inliner {
+        temporaries,
+        uses: counter.uses,
+    }
+    .visit_statements(body);
+}
+
+fn temporary_index(temporaries: &[String], reference: &ReferenceExpression) -> Option<usize> {
+    match reference {
+        ReferenceExpression::Identifier(name) => temporaries.iter().position(|it| it == name),
+        _ => None,
+    }
+}
+
+struct DropPoppedCopies<'a> {
+    temporaries: &'a [String],
+}
+
+impl VisitMut for DropPoppedCopies<'_> {
+    fn visit_statements(&mut self, statements: &mut Vec<Statement>) {
+        statements.retain(|statement| {
+            !matches!(
+                statement,
+                Statement::ExpressionStatement(Expression::Reference(reference))
+                    if temporary_index(self.temporaries, reference).is_some()
+            )
+        });
+        walk_statements(self, statements)
+    }
+
+    fn visit_expression(&mut self, expression: &mut Expression) {
+        if !matches!(expression, Expression::Function { .. }) {
+            walk_expression(self, expression)
+        }
+    }
+}
+
+struct UseCounter<'a> {
+    temporaries: &'a [String],
+    uses: Vec<usize>,
+}
+
+impl VisitMut for UseCounter<'_> {
+    fn visit_statement(&mut self, statement: &mut Statement) {
+        match statement {
+            Statement::DefineLocal { left, right }
+                if temporary_index(self.temporaries, left).is_some() =>
+            {
+                self.visit_expression(right)
+            }
+            _ => walk_statement(self, statement),
+        }
+    }
+
+    fn visit_expression(&mut self, expression: &mut Expression) {
+        // nested functions have their own temporaries
+        if !matches!(expression, Expression::Function { .. }) {
+            walk_expression(self, expression)
+        }
+    }
+
+    fn visit_reference(&mut self, reference: &mut ReferenceExpression) {
+        if let Some(index) = temporary_index(self.temporaries, reference) {
+            self.uses[index] += 1;
+        } else {
+            walk_reference(self, reference)
+        }
+    }
+}
+
+struct Inliner<'a> {
+    temporaries: &'a [String],
+    uses: Vec<usize>,
+}
+
+impl Inliner<'_> {
+    fn is_used_by(&self, index: usize, statement: &mut Statement) -> bool {
+        let mut counter = UseCounter {
+            temporaries: self.temporaries,
+            uses: vec![0; self.temporaries.len()],
+        };
+        counter.visit_statement(statement);
+        counter.uses[index] == 1
+    }
+
+    fn is_assigned_by(&self, index: usize, statement: &Statement) -> bool {
+        matches!(
+            statement.assigned_value(),
+            Some(Expression::Reference(reference))
+                if temporary_index(self.temporaries, reference) == Some(index)
+        )
+    }
+
+    fn substitute(&self, index: usize, value: Expression, statement: &mut Statement) {
+        Substitute {
+            name: &self.temporaries[index],
+            value: Some(value),
+        }
+        .visit_statement(statement)
+    }
+}
+
+impl VisitMut for Inliner<'_> {
+    fn visit_statements(&mut self, statements: &mut Vec<Statement>) {
+        let mut i = 0;
+        while i < statements.len() {
+            let index = match &statements[i] {
+                Statement::DefineLocal { left, .. } => temporary_index(self.temporaries, left),
+                _ => None,
+            };
+            if let Some(index) = index {
+                let remaining = statements.len() - i - 1;
+                match self.uses[index] {
+                    0 => {
+                        let value = take_value(statements.remove(i));
+                        statements.insert(i, Statement::ExpressionStatement(value));
+                    }
+                    1 if remaining >= 1 && self.is_used_by(index, &mut statements[i + 1]) => {
+                        let value = take_value(statements.remove(i));
+                        self.substitute(index, value, &mut statements[i]);
+                    }
+                    // `var t = f(); x = t; ...t...` is an embedded assignment
+                    2 if remaining >= 2
+                        && self.is_assigned_by(index, &statements[i + 1])
+                        && self.is_used_by(index, &mut statements[i + 2]) =>
+                    {
+                        let value = take_value(statements.remove(i));
+                        let mut assignment = statements
+                            .remove(i)
+                            .into_assign_expression()
+                            .expect("Checked by is_assigned_by");
+                        if let Expression::Assign {
+                            value: assigned, ..
+                        } = &mut assignment
+                        {
+                            **assigned = value
+                        }
+                        self.substitute(index, assignment, &mut statements[i]);
+                    }
+                    _ => {}
+                }
+            }
+            i += 1;
+        }
+        walk_statements(self, statements)
+    }
+
+    fn visit_expression(&mut self, expression: &mut Expression) {
+        if !matches!(expression, Expression::Function { .. }) {
+            walk_expression(self, expression)
+        }
+    }
+}
+
+fn take_value(statement: Statement) -> Expression {
+    match statement {
+        Statement::DefineLocal { right, .. } => right,
+        _ => unreachable!("Temporaries are always defined through DefineLocal"),
+    }
+}
+
+struct Substitute<'a> {
+    name: &'a str,
+    value: Option<Expression>,
+}
+
+impl Substitute<'_> {
+    fn matches(&self, reference: &ReferenceExpression) -> bool {
+        matches!(reference, ReferenceExpression::Identifier(name) if name == self.name)
+    }
+}
+
+impl VisitMut for Substitute<'_> {
+    fn visit_expression(&mut self, expression: &mut Expression) {
+        match expression {
+            Expression::Reference(reference) if self.matches(reference) => {
+                if let Some(value) = self.value.take() {
+                    *expression = value
+                }
+            }
+            Expression::Function { .. } => {}
+            _ => walk_expression(self, expression),
+        }
+    }
+
+    fn visit_reference(&mut self, reference: &mut ReferenceExpression) {
+        if self.matches(reference) {
+            if let Some(value) = self.value.take() {
+                *reference = ReferenceExpression::from_expression(value)
+            }
+        } else {
+            walk_reference(self, reference)
+        }
+    }
+}
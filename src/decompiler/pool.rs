@@ -0,0 +1,163 @@
+use crate::ast::variant::Variant;
+use serde_json::Value;
+
+/// A constant pool string as WTF-8, which is UTF-8 extended to the
+/// lone surrogates that the original UTF-16 strings can contain
+pub type PoolString = Vec<u8>;
+
+/// Parses the constant pool JSON. Serde rejects lone surrogates, so every
+/// string is split into an array around them first, like `["a", 55296, "b"]`
+/// for `"a\uD800b"`, and the pieces are put together as WTF-8.
+pub fn parse_constant_pool(json: &str) -> serde_json::Result<Vec<PoolString>> {
+    let strings: Vec<Vec<Value>> = serde_json::from_str(&split_lone_surrogates(json))?;
+    Ok(strings.iter().map(|pieces| join_pieces(pieces)).collect())
+}
+
+/// The literal for a string from the pool or the bytecode. Invalid UTF-8
+/// that isn't WTF-8 either is replaced, as the swf crate would.
+pub fn string_variant(bytes: &[u8]) -> Variant {
+    match std::str::from_utf8(bytes) {
+        Ok(string) => Variant::String(string.to_string()),
+        Err(_) => match decode_wtf8(bytes) {
+            Some(units) => Variant::IllFormedString(units),
+            None => Variant::String(String::from_utf8_lossy(bytes).into_owned()),
+        },
+    }
+}
+
+fn split_lone_surrogates(json: &str) -> String {
+    let mut result = String::with_capacity(json.len());
+    let mut rest = json;
+    let mut in_string = false;
+    while let Some(index) = rest.find(['"', '\\']) {
+        result.push_str(&rest[..index]);
+        rest = &rest[index..];
+
+        if rest.starts_with('"') {
+            result.push_str(if in_string { "\"]" } else { "[\"" });
+            in_string = !in_string;
+            rest = &rest[1..];
+            continue;
+        }
+
+        let unit = parse_unicode_escape(rest);
+        let next = parse_unicode_escape(rest.get(6..).unwrap_or_default());
+        match (unit, next) {
+            (Some(0xD800..=0xDBFF), Some(0xDC00..=0xDFFF)) => {
+                result.push_str(&rest[..12]);
+                rest = &rest[12..];
+            }
+            (Some(unit @ 0xD800..=0xDFFF), _) => {
+                result.push_str(&format!("\", {}, \"", unit));
+                rest = &rest[6..];
+            }
+            _ => {
+                // any other escape, copied with the escaped character
+                let length = rest[1..].chars().next().map_or(0, char::len_utf8) + 1;
+                result.push_str(&rest[..length]);
+                rest = &rest[length..];
+            }
+        }
+    }
+    result.push_str(rest);
+    result
+}
+
+/// Reads the code unit of a `\uXXXX` escape at the start of `input`
+fn parse_unicode_escape(input: &str) -> Option<u16> {
+    let hex = input.strip_prefix("\\u")?.get(..4)?;
+    u16::from_str_radix(hex, 16).ok()
+}
+
+fn join_pieces(pieces: &[Value]) -> PoolString {
+    let mut bytes = Vec::new();
+    for piece in pieces {
+        match piece {
+            Value::String(string) => bytes.extend_from_slice(string.as_bytes()),
+            Value::Number(unit) => {
+                // encoded like any other code point in the BMP
+                let unit = unit.as_u64().unwrap_or_default() as u16;
+                bytes.extend([
+                    0xE0 | (unit >> 12) as u8,
+                    0x80 | (unit >> 6 & 0x3F) as u8,
+                    0x80 | (unit & 0x3F) as u8,
+                ]);
+            }
+            _ => {}
+        }
+    }
+    bytes
+}
+
+/// The UTF-16 code units of a WTF-8 string
+fn decode_wtf8(bytes: &[u8]) -> Option<Vec<u16>> {
+    let mut units = Vec::with_capacity(bytes.len());
+    let mut rest = bytes;
+    loop {
+        match std::str::from_utf8(rest) {
+            Ok(string) => {
+                units.extend(string.encode_utf16());
+                return Some(units);
+            }
+            Err(error) => {
+                let (valid, invalid) = rest.split_at(error.valid_up_to());
+                units.extend(std::str::from_utf8(valid).ok()?.encode_utf16());
+                match invalid {
+                    [0xED, second @ 0xA0..=0xBF, third @ 0x80..=0xBF, tail @ ..] => {
+                        units.push(0xD000 | (*second as u16 & 0x3F) << 6 | (*third as u16 & 0x3F));
+                        rest = tail;
+                    }
+                    _ => return None,
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(json: &str) -> Vec<Variant> {
+        parse_constant_pool(json)
+            .unwrap()
+            .iter()
+            .map(|it| string_variant(it))
+            .collect()
+    }
+
+    fn printed(json: &str) -> Vec<String> {
+        parse(json).iter().map(|it| it.to_string()).collect()
+    }
+
+    #[test]
+    fn keeps_lone_surrogates() {
+        assert_eq!(
+            printed(r#"["a\uD800", "\uDC00b", "\uD800\uD800"]"#),
+            [r#""a\uD800""#, r#""\uDC00b""#, r#""\uD800\uD800""#]
+        );
+        assert!(matches!(
+            &parse(r#"["a\uDBFFb"]"#)[0],
+            Variant::IllFormedString(units) if units == &[0x61, 0xDBFF, 0x62]
+        ));
+    }
+
+    #[test]
+    fn keeps_surrogate_pairs_and_other_escapes() {
+        assert_eq!(
+            parse_constant_pool(r#"["\uD83D\uDE00", "A\n\\uD800\"", "é", ""]"#).unwrap(),
+            ["😀".as_bytes(), b"A\n\\uD800\"", "é".as_bytes(), b""]
+        );
+    }
+
+    #[test]
+    fn keeps_private_use_characters() {
+        // U+F0041, escaped and as it is
+        let json = "[\"\\uDB80\\uDC41\", \"\u{F0041}\"]";
+        assert_eq!(
+            parse_constant_pool(json).unwrap(),
+            ["\u{F0041}".as_bytes(), "\u{F0041}".as_bytes()]
+        );
+        assert_eq!(printed(json), ["\"\u{F0041}\"", "\"\u{F0041}\""]);
+    }
+}
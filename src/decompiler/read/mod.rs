@@ -1,7 +1,9 @@
+use crate::decompiler::opcodes::OpcodeDecoder;
+use crate::decompiler::pool::PoolString;
 use crate::decompiler::read::read_define_function::{read_define_function, read_define_function_2};
 use crate::decompiler::read::read_push::read_push;
 use swf::avm1::read::Reader;
-use swf::avm1::types::Action;
+use swf::avm1::types::{Action, Unknown};
 use swf::error::Error;
 use swf::extensions::ReadSwfExt;
 
@@ -9,10 +11,14 @@ mod read_define_function;
 mod read_push;
 mod read_str;
 
+/// Flash Lite's `ActionFSCommand2`, which the swf crate doesn't know about
+pub const FS_COMMAND_2: u8 = 0x2d;
+
 pub fn read<'a>(
     reader: &mut Reader<'a>,
     input: &'a [u8],
-    symbols: &'a [String],
+    symbols: &'a [PoolString],
+    opcodes: Option<&dyn OpcodeDecoder>,
 ) -> Result<Action<'a>, Error> {
     let pos = reader.pos(input);
     let opcode = reader.read_u8()?;
@@ -22,6 +28,18 @@ pub fn read<'a>(
         0
     };
 
+    // proprietary opcodes take precedence, even over the ones overridden below
+    if let Some(decoder) = opcodes {
+        let rest = reader.get_ref();
+        if decoder
+            .stack_effect(opcode, &rest[..length.min(rest.len())])
+            .is_some()
+        {
+            let data = reader.read_slice(length)?;
+            return Ok(Action::Unknown(Unknown { opcode, data }));
+        }
+    }
+
     match opcode {
         0x96 => Ok(Action::Push(read_push(reader, length, symbols)?)),
         0x8e => Ok(Action::DefineFunction2(read_define_function_2(
@@ -34,6 +52,7 @@ pub fn read<'a>(
             &mut length,
             symbols,
         )?)),
+        FS_COMMAND_2 => Ok(Action::Unknown(Unknown { opcode, data: &[] })),
         _ => {
             reader.seek_absolute(input, pos);
             reader.read_action()
@@ -1,3 +1,4 @@
+use crate::decompiler::pool::PoolString;
 use crate::decompiler::read::read_str::read_str;
 use std::num::NonZeroU8;
 use swf::avm1::read::Reader;
@@ -8,7 +9,7 @@ use swf::extensions::ReadSwfExt;
 pub fn read_define_function<'a>(
     reader: &mut Reader<'a>,
     action_length: &mut usize,
-    symbols: &'a [String],
+    symbols: &'a [PoolString],
 ) -> Result<DefineFunction<'a>, Error> {
     let name = read_str(reader, symbols)?;
     let num_params = reader.read_u16()?;
@@ -29,7 +30,7 @@ pub fn read_define_function<'a>(
 pub fn read_define_function_2<'a>(
     reader: &mut Reader<'a>,
     action_length: &mut usize,
-    symbols: &'a [String],
+    symbols: &'a [PoolString],
 ) -> Result<DefineFunction2<'a>, Error> {
     let name = read_str(reader, symbols)?;
     let num_params = reader.read_u16()?;
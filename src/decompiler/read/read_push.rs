@@ -1,3 +1,4 @@
+use crate::decompiler::pool::PoolString;
 use crate::decompiler::read::read_str::read_str;
 use swf::avm1::read::Reader;
 use swf::avm1::types::{Push, Value};
@@ -11,7 +12,7 @@ use swf::extensions::ReadSwfExt;
 pub fn read_push<'a>(
     reader: &mut Reader<'a>,
     length: usize,
-    symbols: &'a [String],
+    symbols: &'a [PoolString],
 ) -> Result<Push<'a>> {
     // TODO: Verify correct version for complex types.
     let end_pos = (reader.get_ref().as_ptr() as usize + length) as *const u8;
@@ -26,7 +27,7 @@ pub fn read_push<'a>(
 /// LMD ActionScript, for some reason, decided to add all
 /// strings to the constant pool aka symbols section
 /// so the stock reader will crash on some
-fn read_push_value<'a>(reader: &mut Reader<'a>, symbols: &'a [String]) -> Result<Value<'a>> {
+fn read_push_value<'a>(reader: &mut Reader<'a>, symbols: &'a [PoolString]) -> Result<Value<'a>> {
     let value = match reader.read_u8()? {
         0 => Value::Str(read_str(reader, symbols)?),
         1 => Value::Float(reader.read_f32()?),
@@ -0,0 +1,326 @@
+use crate::ast::expr::{Expression, ReferenceExpression};
+use crate::ast::identifier::{is_array_index, is_identifier_name, is_valid_variable_name};
+use crate::ast::statement::Statement;
+use crate::ast::variant::Variant;
+use crate::ast::visit::{walk_expression, walk_reference, walk_statement, VisitMut};
+use crate::decompiler::pool::PoolString;
+use std::collections::{BTreeMap, HashSet};
+
+/// Replaces obfuscated identifiers with generated `_obf_N` names.
+///
+/// Only declared variables, functions and parameters are renamed, as other
+/// names can be set from outside, like instance names on the stage.
+/// The mapping is keyed on the original constant pool strings and kept
+/// across runs, so the same name gets the same replacement in every
+/// version of the content.
+#[derive(Debug, Default)]
+pub struct Renamer {
+    mapping: BTreeMap<String, String>,
+    used: HashSet<String>,
+    /// Names declared in the actions renamed so far
+    declared: HashSet<String>,
+    next: usize,
+    /// Also rename member names and object keys
+    pub members: bool,
+}
+
+impl Renamer {
+    pub fn from_json(json: &str) -> serde_json::Result<Renamer> {
+        let mapping: BTreeMap<String, String> = serde_json::from_str(json)?;
+        let used = mapping.values().cloned().collect();
+        Ok(Renamer {
+            next: mapping.len(),
+            mapping,
+            used,
+            ..Default::default()
+        })
+    }
+
+    pub fn to_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string_pretty(&self.mapping)
+    }
+
+    /// Names that aren't in `constant_pool` are left alone, and
+    /// generated names never collide with the ones that are
+    pub fn rename(&mut self, statements: &mut Vec<Statement>, constant_pool: &[PoolString]) {
+        Declarations(&mut self.declared).visit_statements(statements);
+        Rename {
+            renamer: self,
+            constant_pool: constant_pool
+                .iter()
+                .filter_map(|it| std::str::from_utf8(it).ok())
+                .collect(),
+        }
+        .visit_statements(statements)
+    }
+}
+
+/// Collects the names of `var` declarations, functions and parameters
+struct Declarations<'a>(&'a mut HashSet<String>);
+
+impl VisitMut for Declarations<'_> {
+    fn visit_statement(&mut self, statement: &mut Statement) {
+        if let Statement::DefineLocal {
+            left: ReferenceExpression::Identifier(name),
+            ..
+        }
+        | Statement::DeclareLocal {
+            name: ReferenceExpression::Identifier(name),
+        } = statement
+        {
+            self.0.insert(name.clone());
+        }
+        walk_statement(self, statement)
+    }
+
+    fn visit_expression(&mut self, expression: &mut Expression) {
+        if let Expression::Function {
+            identifier,
+            parameters,
+            ..
+        } = expression
+        {
+            self.0.extend(identifier.clone());
+            for parameter in parameters.iter() {
+                if let ReferenceExpression::Identifier(name) = parameter {
+                    self.0.insert(name.clone());
+                }
+            }
+        }
+        walk_expression(self, expression)
+    }
+}
+
+struct Rename<'a> {
+    renamer: &'a mut Renamer,
+    constant_pool: HashSet<&'a str>,
+}
+
+impl Rename<'_> {
+    /// `member` is set for member names and object keys, which can be reserved words
+    fn rename_name(&mut self, name: &mut String, member: bool) {
+        let renamer = &mut *self.renamer;
+        let is_renamed = if member {
+            renamer.members
+        } else {
+            renamer.declared.contains(name.as_str())
+        };
+        if !is_renamed
+            || !self.constant_pool.contains(name.as_str())
+            || !is_suspicious(name, member)
+        {
+            return;
+        }
+        if let Some(renamed) = renamer.mapping.get(name) {
+            *name = renamed.clone();
+            return;
+        }
+
+        let renamed = loop {
+            let candidate = format!("_obf_{}", renamer.next);
+            renamer.next += 1;
+            if !self.constant_pool.contains(candidate.as_str())
+                && renamer.used.insert(candidate.clone())
+            {
+                break candidate;
+            }
+        };
+        renamer.mapping.insert(name.clone(), renamed.clone());
+        *name = renamed;
+    }
+}
+
+/// Names that can't be written as identifiers, contain invisible
+/// characters or mix Latin letters with look-alikes from other scripts.
+/// Array indices like `0` are left alone.
+fn is_suspicious(name: &str, member: bool) -> bool {
+    if is_array_index(name) {
+        return false;
+    }
+    let is_valid = if member {
+        is_identifier_name(name)
+    } else {
+        is_valid_variable_name(name)
+    };
+    let has_invisible = name.chars().any(|it| {
+        it.is_control() || matches!(it, '\u{200B}'..='\u{200F}' | '\u{2060}' | '\u{FEFF}')
+    });
+    let has_latin = name.chars().any(|it| it.is_ascii_alphabetic());
+    let has_look_alike = name
+        .chars()
+        .any(|it| matches!(it, '\u{0370}'..='\u{04FF}' | '\u{FF01}'..='\u{FF5E}'));
+    !is_valid || has_invisible || (has_latin && has_look_alike)
+}
+
+impl VisitMut for Rename<'_> {
+    fn visit_statement(&mut self, statement: &mut Statement) {
+        walk_statement(self, statement);
+        // `set("name", v)` is only needed as long as the name is invalid
+        if let Statement::ExpressionStatement(Expression::CallFunction { name, args }) = statement {
+            if let [Expression::Literal(Variant::String(variable)), value] = args.as_mut_slice() {
+                if name_is(name, "set") && is_valid_variable_name(variable) {
+                    *statement = Statement::SetVariable {
+                        left: ReferenceExpression::Variable(std::mem::take(variable)),
+                        right: Box::new(value.clone()),
+                    };
+                }
+            }
+        }
+    }
+
+    fn visit_expression(&mut self, expression: &mut Expression) {
+        walk_expression(self, expression);
+        match expression {
+            Expression::Function {
+                identifier: Some(identifier),
+                ..
+            } => self.rename_name(identifier, false),
+            Expression::Literal(Variant::Object(properties)) => {
+                for (key, _) in properties.iter_mut() {
+                    if let Expression::Literal(Variant::String(key)) = key {
+                        self.rename_name(key, true)
+                    }
+                }
+            }
+            Expression::CallFunction { name, args } if is_variable_call(name, args) => {
+                if let Expression::Literal(Variant::String(variable)) = &mut args[0] {
+                    self.rename_name(variable, false);
+                    if name_is(name, "eval") && is_valid_variable_name(variable) {
+                        *expression =
+                            Expression::Reference(ReferenceExpression::Variable(variable.clone()));
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn visit_reference(&mut self, reference: &mut ReferenceExpression) {
+        match reference {
+            ReferenceExpression::Identifier(name) | ReferenceExpression::Variable(name) => {
+                self.rename_name(name, false)
+            }
+            reference => walk_reference(self, reference),
+        }
+    }
+
+    fn visit_member_name(&mut self, name: &mut ReferenceExpression) {
+        match name {
+            ReferenceExpression::Identifier(name) => self.rename_name(name, true),
+            name => self.visit_reference(name),
+        }
+    }
+}
+
+fn name_is(name: &ReferenceExpression, expected: &str) -> bool {
+    matches!(name, ReferenceExpression::Identifier(it) if it == expected)
+}
+
+/// `eval("name")` and `set("name", v)` with a constant name
+fn is_variable_call(name: &ReferenceExpression, args: &[Expression]) -> bool {
+    let arity = if name_is(name, "eval") {
+        1
+    } else if name_is(name, "set") {
+        2
+    } else {
+        return false;
+    };
+    args.len() == arity && matches!(args[0], Expression::Literal(Variant::String(_)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn identifier(name: &str) -> ReferenceExpression {
+        ReferenceExpression::Identifier(name.to_string())
+    }
+
+    /// `var name = 1; trace(name); obj[name] = 2`
+    fn declare_and_use(name: &str) -> Vec<Statement> {
+        vec![
+            Statement::DefineLocal {
+                left: identifier(name),
+                right: Expression::Literal(Variant::Int(1)),
+            },
+            Statement::Trace(Expression::Reference(identifier(name))),
+            Statement::SetMember {
+                object: identifier("obj"),
+                name: identifier(name),
+                value: Expression::Literal(Variant::Int(2)),
+            },
+        ]
+    }
+
+    fn pool(names: &[&str]) -> Vec<PoolString> {
+        names.iter().map(|it| it.as_bytes().to_vec()).collect()
+    }
+
+    fn print(statements: &[Statement]) -> Vec<String> {
+        statements.iter().map(|it| it.to_string()).collect()
+    }
+
+    #[test]
+    fn renames_declared_variables_but_not_members() {
+        let pool = pool(&["obj", "a-b"]);
+        let mut statements = declare_and_use("a-b");
+        Renamer::default().rename(&mut statements, &pool);
+        assert_eq!(
+            print(&statements),
+            ["var _obf_0 = 1", "trace(_obf_0)", "obj[\"a-b\"] = 2"]
+        );
+
+        let mut statements = declare_and_use("a-b");
+        let mut renamer = Renamer {
+            members: true,
+            ..Default::default()
+        };
+        renamer.rename(&mut statements, &pool);
+        assert_eq!(print(&statements)[2], "obj._obf_0 = 2");
+    }
+
+    #[test]
+    fn keeps_the_mapping_stable_across_runs() {
+        let pool = pool(&["obj", "a-b", "if", "\u{1}x"]);
+        let mut first = Renamer::default();
+        let mut statements = declare_and_use("\u{1}x");
+        first.rename(&mut statements, &pool);
+        let mut more = declare_and_use("a-b");
+        first.rename(&mut more, &pool);
+        let json = first.to_json().unwrap();
+
+        // a later version declares the names in a different order
+        let mut second = Renamer::from_json(&json).unwrap();
+        let mut later = declare_and_use("if");
+        later.extend(declare_and_use("a-b"));
+        later.extend(declare_and_use("\u{1}x"));
+        second.rename(&mut later, &pool);
+
+        assert_eq!(print(&later)[4], "trace(_obf_1)");
+        assert_eq!(print(&later)[7], "trace(_obf_0)");
+        assert_eq!(print(&later)[1], "trace(_obf_2)");
+        let mapping: BTreeMap<String, String> =
+            serde_json::from_str(&second.to_json().unwrap()).unwrap();
+        assert_eq!(mapping["\u{1}x"], "_obf_0");
+        assert_eq!(mapping["a-b"], "_obf_1");
+    }
+
+    #[test]
+    fn never_generates_a_name_from_the_source() {
+        let pool = pool(&["obj", "_obf_0", "_obf_1", "a-b"]);
+        let mut statements = declare_and_use("a-b");
+        statements.extend([Statement::Trace(Expression::Reference(identifier(
+            "_obf_0",
+        )))]);
+        Renamer::default().rename(&mut statements, &pool);
+        assert_eq!(print(&statements)[1], "trace(_obf_2)");
+        assert_eq!(print(&statements)[3], "trace(_obf_0)");
+    }
+
+    #[test]
+    fn leaves_names_outside_of_the_pool() {
+        let mut statements = declare_and_use("a-b");
+        Renamer::default().rename(&mut statements, &pool(&["obj"]));
+        assert_eq!(print(&statements)[1], "trace(eval(\"a-b\"))");
+    }
+}
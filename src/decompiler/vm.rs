@@ -1,17 +1,28 @@
-use crate::ast::expr::Expression;
+use crate::ast::expr::{Expression, ReferenceExpression};
 use crate::ast::statement::Statement;
 use crate::ast::variant::Variant;
 use crate::decompiler::components::jump_logger::{log_jump, log_return};
+use crate::decompiler::passes::compound_assignment::recover_compound_assignments;
+use crate::decompiler::passes::registers::strip_unread_registers;
+use crate::decompiler::passes::simplify::simplify_expressions;
+use crate::decompiler::passes::temporaries::inline_temporaries;
+use crate::decompiler::pool::string_variant;
 use crate::decompiler::read::read;
+use crate::decompiler::vm::frame_loaded::{
+    close_frame_loaded, count_frame_loaded_action, open_frame_loaded, FrameLoadedBlock,
+};
 use crate::decompiler::vm::loops::resolve_loop;
-use crate::decompiler::VmData;
+use crate::decompiler::vm::tell_target::{close_tell_target, open_tell_target, TellTargetBlock};
+use crate::decompiler::{Register, VmData};
 use std::borrow::Cow;
 use swf::avm1::read::Reader;
-use swf::avm1::types::Action;
+use swf::avm1::types::{Action, Unknown};
 use swf::error::{Error, Result};
 use swf::extensions::ReadSwfExt;
 
+mod frame_loaded;
 mod loops;
+mod tell_target;
 
 impl<'a> From<VmData<'a>> for VirtualMachine<'a> {
     fn from(value: VmData<'a>) -> Self {
@@ -19,9 +30,13 @@ impl<'a> From<VmData<'a>> for VirtualMachine<'a> {
             reader: Reader::new(value.bytecode, 1),
             stack: vec![],
             block: vec![],
-            pending_branches: vec![],
+            temporaries: vec![],
+            frame_loaded: vec![],
+            tell_target: None,
             data: value,
             offset: 0,
+            action: 0,
+            jump_targets: vec![],
         }
     }
 }
@@ -29,15 +44,26 @@ impl<'a> From<VmData<'a>> for VirtualMachine<'a> {
 pub struct VirtualMachine<'a> {
     stack: Vec<(usize, Expression)>,
     block: Vec<(usize, Statement)>,
-    pending_branches: Vec<(usize, usize)>,
+    temporaries: Vec<String>,
+    frame_loaded: Vec<FrameLoadedBlock>,
+    tell_target: Option<TellTargetBlock>,
     reader: Reader<'a>,
     offset: usize,
+    /// Where the action being decompiled starts
+    action: usize,
+    /// Offsets that forward jumps seen so far lead to
+    jump_targets: Vec<usize>,
     pub data: VmData<'a>,
 }
 
 impl<'a> VirtualMachine<'a> {
+    /// Offset of the action being decompiled, or of the last value popped for it
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
     pub fn pop(&mut self) -> Result<Expression> {
-        let (new_offset, value) = if self.data.strict {
+        let (new_offset, value) = if self.data.options.strict {
             self.stack
                 .pop()
                 .ok_or(Error::InvalidData(Cow::from("Tried to pop empty stack")))?
@@ -51,6 +77,30 @@ impl<'a> VirtualMachine<'a> {
         Ok(value)
     }
 
+    pub fn peek(&self) -> Option<&Expression> {
+        self.stack.last().map(|(_, value)| value)
+    }
+
+    /// The value `depth` entries below the top of the stack
+    pub fn peek_at(&self, depth: usize) -> Option<&Expression> {
+        let index = self.stack.len().checked_sub(depth + 1)?;
+        self.stack.get(index).map(|(_, value)| value)
+    }
+
+    /// `ActionStackSwap`, swaps the two topmost values along with their offsets
+    pub fn swap(&mut self) -> Result<()> {
+        let len = self.stack.len();
+        if len >= 2 {
+            self.stack.swap(len - 1, len - 2);
+            return Ok(());
+        }
+        let top = self.pop()?;
+        let below = self.pop()?;
+        self.push(top);
+        self.push(below);
+        Ok(())
+    }
+
     pub fn pop_len(&mut self, len: usize) -> Result<Vec<Expression>> {
         let mut vec = Vec::<Expression>::with_capacity(len);
         for _ in 0..len {
@@ -59,22 +109,38 @@ impl<'a> VirtualMachine<'a> {
         Ok(vec)
     }
 
-    pub fn store(&mut self, register_id: u8, value: Expression) -> Result<()> {
+    /// Pops an argument count followed by that many arguments,
+    /// as used by the call and constructor actions
+    pub fn pop_args(&mut self) -> Result<Vec<Expression>> {
+        let num_args = match self.pop()? {
+            Expression::Literal(Variant::Int(i)) if i >= 0 => i as usize,
+            Expression::Literal(Variant::Float(f)) if f >= 0.0 && f.fract() == 0.0 => f as usize,
+            Expression::Literal(Variant::Double(f)) if f >= 0.0 && f.fract() == 0.0 => f as usize,
+            _ => {
+                return Err(Error::invalid_data(
+                    "Tried calling a function with non-constant arg count",
+                ))
+            }
+        };
+        self.pop_len(num_args)
+    }
+
+    /// The parameter or preloaded value a register holds, if any
+    pub fn register_name(&self, register_id: u8) -> Option<&str> {
+        match self.data.registers.get(register_id as usize)? {
+            Register::Parameter(name) => Some(name),
+            Register::Preloaded(name) => Some(name),
+            Register::Unnamed => None,
+        }
+    }
+
+    pub fn store(&mut self, register_id: u8) -> Result<()> {
         let i = register_id as usize;
-        if (0..self.data.registers.len()).contains(&i) {
-            self.data.registers[i] = value;
-            Ok(())
-        } else if self.data.strict {
+        if i >= self.data.registers.len() && self.data.options.strict {
             Err(Error::InvalidData(Cow::from(
                 "Tried to set non-existent register",
             )))
         } else {
-            while self.data.registers.len() < i {
-                self.data
-                    .registers
-                    .push(Expression::Literal(Variant::Uninitialized));
-            }
-            self.data.registers.push(value);
             Ok(())
         }
     }
@@ -83,25 +149,115 @@ impl<'a> VirtualMachine<'a> {
         self.stack.push((self.offset, expression));
     }
 
+    /// Stores `value` in a fresh temporary local and returns its name
+    pub fn store_temporary(&mut self, value: Expression) -> String {
+        let name = format!("$t{}", self.temporaries.len());
+        self.temporaries.push(name.clone());
+        self.append_statement(Statement::DefineLocal {
+            left: ReferenceExpression::Identifier(name.clone()),
+            right: value,
+        });
+        name
+    }
+
     pub fn append_statement(&mut self, statement: Statement) {
         self.block.push((self.offset, statement));
     }
 
+    /// Removes the last statement if it matches the predicate and
+    /// nothing on the stack was pushed after it
+    pub fn take_last_statement<P>(&mut self, predicate: P) -> Option<Statement>
+    where
+        P: FnOnce(&Statement) -> bool,
+    {
+        let (pos, statement) = self.block.last()?;
+        if self.stack.iter().all(|(it, _)| it <= pos)
+            && *pos >= self.open_block_start()
+            && predicate(statement)
+        {
+            self.block.pop().map(|(_, statement)| statement)
+        } else {
+            None
+        }
+    }
+
+    /// The last statement, unless it comes before the innermost
+    /// `ifFrameLoaded` or `tellTarget` block that is still open
+    pub fn last_statement_mut(&mut self) -> Option<&mut Statement> {
+        let start = self.open_block_start();
+        self.block
+            .last_mut()
+            .filter(|(pos, _)| *pos >= start)
+            .map(|(_, statement)| statement)
+    }
+
+    fn open_block_start(&self) -> usize {
+        let frame_loaded = self.frame_loaded.last().map(|block| block.pos);
+        let tell_target = self.tell_target.as_ref().map(|block| block.pos);
+        frame_loaded.max(tell_target).unwrap_or(0)
+    }
+
+    /// Whether a jump leads to the action being decompiled, so that
+    /// it can be reached without running the statements before it
+    pub fn is_jump_target(&self) -> bool {
+        self.jump_targets.contains(&self.action)
+    }
+
+    /// Removes the statements at or after `pos` for a block opened there.
+    /// `preceding` is how many statements came before the block when it opened,
+    /// a different count means something was restructured across its start.
+    fn take_statements_since(
+        &mut self,
+        pos: usize,
+        preceding: usize,
+        name: &str,
+    ) -> Vec<Statement> {
+        let start = self
+            .block
+            .iter()
+            .rposition(|(offset, _)| *offset < pos)
+            .map_or(0, |index| index + 1);
+        if start != preceding || self.block[..start].iter().any(|(offset, _)| *offset >= pos) {
+            eprintln!(
+                "Statements cross the start of the {} block at offset {}",
+                name, pos
+            );
+        }
+        self.block.drain(start..).map(|it| it.1).collect()
+    }
+
     pub fn read_action(&mut self) -> Result<Action<'a>> {
         self.offset = self.reader.pos(self.data.bytecode);
+        self.action = self.offset;
         read(
             &mut self.reader,
             self.data.bytecode,
             self.data.constant_pool,
+            self.data.opcodes,
         )
     }
 
+    /// Skips the action at the current offset using only its length field,
+    /// for actions that failed to decode. Returns `None` if not even the
+    /// opcode and length could be read.
+    pub fn skip_action(&mut self) -> Option<Unknown<'a>> {
+        self.reader.seek_absolute(self.data.bytecode, self.offset);
+        let (opcode, length) = self.reader.read_opcode_and_length().ok()?;
+        let input: &'a [u8] = self.reader.get_ref();
+        let (data, rest) = input.split_at(length.min(input.len()));
+        *self.reader.get_mut() = rest;
+        Some(Unknown { opcode, data })
+    }
+
     pub fn jump(&mut self, offset: i16, condition: Option<Expression>) -> Result<()> {
         let actual_position = self.reader.pos(self.data.bytecode);
         let position = self.offset;
         let target = (actual_position as i64 + offset as i64) as usize;
 
         log_jump(offset, position, actual_position, target, &condition);
+        if target > actual_position {
+            self.jump_targets.push(target);
+        }
 
         if offset < 0 {
             resolve_loop(self, target)
@@ -131,11 +287,40 @@ impl<'a> VirtualMachine<'a> {
         })
     }
 
-    pub fn get_constant(&mut self, id: usize) -> String {
-        self.data.constant_pool[id].clone()
+    /// Starts an `ifFrameLoaded` block spanning the next `num_actions` actions
+    pub fn wait_for_frame(&mut self, frame: Expression, num_actions: u8) {
+        open_frame_loaded(self, frame, num_actions)
+    }
+
+    /// Opens a `tellTarget` block, or closes the current one if `target` is empty
+    pub fn set_target(&mut self, target: Expression) {
+        close_tell_target(self);
+        if !matches!(&target, Expression::Literal(Variant::String(it)) if it.is_empty()) {
+            open_tell_target(self, target)
+        }
+    }
+
+    /// Has to be called once after every decompiled action
+    pub fn end_action(&mut self) {
+        count_frame_loaded_action(self)
+    }
+
+    pub fn get_constant(&mut self, id: usize) -> Variant {
+        string_variant(&self.data.constant_pool[id])
     }
 
     pub fn finalize(mut self) -> Vec<Statement> {
+        if !self.frame_loaded.is_empty() {
+            eprintln!(
+                "{} unterminated ifFrameLoaded blocks",
+                self.frame_loaded.len()
+            );
+        }
+        while !self.frame_loaded.is_empty() {
+            close_frame_loaded(&mut self);
+        }
+        close_tell_target(&mut self);
+        let temporaries = std::mem::take(&mut self.temporaries);
         if !self.stack.is_empty() {
             eprintln!("{} remaining items on the stack", self.stack.len())
         }
@@ -147,9 +332,17 @@ impl<'a> VirtualMachine<'a> {
             .map(|(pos, expr)| (pos, Statement::DanglingStack(expr)))
             .collect();
         self.block.append(&mut dangling_stack);
-        self.block
+        let mut body = self
+            .block
             .into_iter()
             .map(|(_, statement)| statement)
-            .collect()
+            .collect();
+        inline_temporaries(&mut body, &temporaries);
+        strip_unread_registers(&mut body);
+        if !self.data.options.no_simplify {
+            simplify_expressions(&mut body, &self.data.options);
+        }
+        recover_compound_assignments(&mut body);
+        body
     }
 }
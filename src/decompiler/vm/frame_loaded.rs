@@ -0,0 +1,59 @@
+use crate::ast::block::Block;
+use crate::ast::expr::Expression;
+use crate::ast::statement::Statement;
+use crate::decompiler::vm::VirtualMachine;
+
+/// An `ifFrameLoaded` block that is still collecting actions
+pub struct FrameLoadedBlock {
+    pub(super) pos: usize,
+    preceding: usize,
+    frame: Expression,
+    /// Actions left until the block ends, including the one that opened it
+    remaining: usize,
+}
+
+/// `WaitForFrame` skips the next `num_actions` actions if the frame isn't loaded yet.
+/// The skip count is in actions rather than bytes, so the block
+/// can only be closed by counting the actions that follow.
+pub fn open_frame_loaded(vm: &mut VirtualMachine, frame: Expression, num_actions: u8) {
+    vm.frame_loaded.push(FrameLoadedBlock {
+        pos: vm.offset,
+        preceding: vm.block.len(),
+        frame,
+        remaining: num_actions as usize + 1,
+    })
+}
+
+/// Counts one action towards every open block and closes those that are complete
+pub fn count_frame_loaded_action(vm: &mut VirtualMachine) {
+    for block in vm.frame_loaded.iter_mut() {
+        block.remaining = block.remaining.saturating_sub(1);
+    }
+    while vm
+        .frame_loaded
+        .last()
+        .is_some_and(|block| block.remaining == 0)
+    {
+        close_frame_loaded(vm);
+    }
+}
+
+/// Closes the innermost block with whatever it collected so far
+pub fn close_frame_loaded(vm: &mut VirtualMachine) {
+    if let Some(FrameLoadedBlock {
+        pos,
+        preceding,
+        frame,
+        ..
+    }) = vm.frame_loaded.pop()
+    {
+        let body = vm.take_statements_since(pos, preceding, "ifFrameLoaded");
+        vm.block.push((
+            pos,
+            Statement::IfFrameLoaded {
+                frame,
+                block: Block { body },
+            },
+        ));
+    }
+}
@@ -0,0 +1,38 @@
+use crate::ast::block::Block;
+use crate::ast::expr::Expression;
+use crate::ast::statement::Statement;
+use crate::decompiler::vm::VirtualMachine;
+
+/// A `tellTarget` block that lasts until the next `SetTarget`
+pub struct TellTargetBlock {
+    pub(super) pos: usize,
+    preceding: usize,
+    target: Expression,
+}
+
+pub fn open_tell_target(vm: &mut VirtualMachine, target: Expression) {
+    vm.tell_target = Some(TellTargetBlock {
+        pos: vm.offset,
+        preceding: vm.block.len(),
+        target,
+    })
+}
+
+/// `SetTarget` doesn't nest, every new target ends the previous block
+pub fn close_tell_target(vm: &mut VirtualMachine) {
+    if let Some(TellTargetBlock {
+        pos,
+        preceding,
+        target,
+    }) = vm.tell_target.take()
+    {
+        let body = vm.take_statements_since(pos, preceding, "tellTarget");
+        vm.block.push((
+            pos,
+            Statement::TellTarget {
+                target,
+                block: Block { body },
+            },
+        ));
+    }
+}
@@ -2,9 +2,12 @@ pub mod ast;
 pub mod decompiler;
 
 use crate::ast::action::Action;
-use crate::decompiler::{decompile, VmData};
-use clap::{arg, Parser, Subcommand};
-use dprint_plugin_typescript::configuration::{Configuration, ConfigurationBuilder};
+use crate::decompiler::opcodes::{remap_opcodes, OpcodeTable};
+use crate::decompiler::pool::parse_constant_pool;
+use crate::decompiler::rename::Renamer;
+use crate::decompiler::{decompile, DecompileOptions, VmData};
+use clap::{Parser, Subcommand};
+use dprint_plugin_typescript::configuration::ConfigurationBuilder;
 use dprint_plugin_typescript::format_text;
 use std::fs;
 use std::path::Path;
@@ -26,12 +29,41 @@ enum Commands {
         #[arg(short, long, default_value_t = false)]
         strict: bool,
 
+        /// Fold negated comparisons like `!(a < b)` into `a >= b`
+        /// even when an operand could be NaN
+        #[arg(long, default_value_t = false)]
+        assume_no_nan: bool,
+
+        /// Keep SWF4 paths like `/menu:count` instead of
+        /// rewriting them to `_root.menu.count`
+        #[arg(long, default_value_t = false)]
+        keep_slash_syntax: bool,
+
+        /// Don't fold constant expressions or remove identities like `x * 1`
+        #[arg(long, default_value_t = false)]
+        no_simplify: bool,
+
         /// Specify a path to the constant pool file.
         /// By default it will look for a .json file
         /// with the same name
         #[arg(short, long)]
         pool: Option<std::path::PathBuf>,
 
+        /// Replace obfuscated identifiers with generated names, using and
+        /// updating the mapping from original to generated names in this file
+        #[arg(long)]
+        rename: Option<std::path::PathBuf>,
+
+        /// With --rename, also rename member names and object keys.
+        /// These can be set from outside, so renaming them can break the content.
+        #[arg(long, default_value_t = false, requires = "rename")]
+        rename_members: bool,
+
+        /// JSON file with an opcode permutation table and
+        /// the stack effects of proprietary opcodes
+        #[arg(long)]
+        opcodes: Option<std::path::PathBuf>,
+
         /// Output path of the decompiled result
         /// If not specified it will write next to the input with a .as extension
         #[arg(short, long)]
@@ -46,13 +78,19 @@ fn main() {
     match Args::parse().cmd {
         Commands::Decompile {
             strict,
+            assume_no_nan,
+            keep_slash_syntax,
+            no_simplify,
             path,
             out,
             pool,
+            rename,
+            rename_members,
+            opcodes,
         } => {
             let format_config = ConfigurationBuilder::new().build();
 
-            let pool: Vec<String> = serde_json::from_str(
+            let pool = parse_constant_pool(
                 fs::read_to_string(if let Some(pool_path) = pool {
                     pool_path
                 } else {
@@ -68,6 +106,30 @@ fn main() {
                 path.with_extension("as")
             };
 
+            let mut renamer = rename.as_ref().map(|mapping_path| {
+                let mut renamer = if mapping_path.exists() {
+                    Renamer::from_json(
+                        fs::read_to_string(mapping_path)
+                            .expect("Unreadable mapping file")
+                            .as_str(),
+                    )
+                    .expect("Invalid mapping file")
+                } else {
+                    Renamer::default()
+                };
+                renamer.members = rename_members;
+                renamer
+            });
+
+            let opcodes = opcodes.map(|opcodes_path| {
+                OpcodeTable::from_json(
+                    fs::read_to_string(opcodes_path)
+                        .expect("Missing opcodes file")
+                        .as_str(),
+                )
+                .expect("Invalid opcodes file")
+            });
+
             let data = fs::read(path).expect("Invalid file");
             let mut reader = Reader::new(&data, 1);
 
@@ -78,14 +140,25 @@ fn main() {
                 let action_size = reader.read_u16().unwrap();
                 reader.read_u16().unwrap();
                 let action = reader.read_slice(action_size as usize).unwrap();
-                let action_body = decompile(VmData {
-                    bytecode: action,
+                let remapped = opcodes.as_ref().map(|table| remap_opcodes(action, table));
+                let mut action_body = decompile(VmData {
+                    bytecode: remapped.as_deref().unwrap_or(action),
                     constant_pool: &pool,
-                    strict,
+                    options: DecompileOptions {
+                        strict,
+                        assume_no_nan,
+                        keep_slash_syntax,
+                        no_simplify,
+                    },
                     registers: Vec::new(),
+                    opcodes: opcodes.as_ref().map(|table| table as _),
                 })
                 .expect("Decompile failed");
 
+                if let Some(renamer) = renamer.as_mut() {
+                    renamer.rename(&mut action_body, &pool);
+                }
+
                 let decompiled_action = Action {
                     id: i,
                     statements: action_body,
@@ -102,14 +175,22 @@ fn main() {
                     }
                     .unwrap_or(emitted_code);
                 fs::write(
-                    &out_path.with_extension(format!("{}.as", decompiled_action.id)),
+                    out_path.with_extension(format!("{}.as", decompiled_action.id)),
                     formatted,
                 )
                 .unwrap();
                 if (action_size + 4) % 4 != 0 {
-                    reader.read_slice(4 - (action_size as usize + 4) % 4);
+                    let _ = reader.read_slice(4 - (action_size as usize + 4) % 4);
                 }
             }
+
+            if let (Some(mapping_path), Some(renamer)) = (rename, renamer) {
+                fs::write(
+                    mapping_path,
+                    renamer.to_json().expect("Mapping is always serializable"),
+                )
+                .expect("Unwritable mapping file");
+            }
         }
     }
 }